@@ -0,0 +1,65 @@
+// A std-only benchmark: criterion can't be pulled in here, so this times the
+// parse loop by hand and counts heap allocations with a wrapping allocator.
+// Run it with `cargo bench`.
+extern crate rshttp;
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::hint::black_box;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use rshttp::HttpRequest;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const BROWSER_REQUEST: &str =
+    "GET /search?q=rust+http+parser&ie=UTF-8 HTTP/1.1\r\n\
+     Host: www.example.com\r\n\
+     Connection: keep-alive\r\n\
+     Upgrade-Insecure-Requests: 1\r\n\
+     User-Agent: Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 \
+     (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36\r\n\
+     Accept: text/html,application/xhtml+xml,application/xml;q=0.9,\
+     image/avif,image/webp,*/*;q=0.8\r\n\
+     Referer: https://www.example.com/\r\n\
+     Accept-Encoding: gzip, deflate, br\r\n\
+     Accept-Language: en-US,en;q=0.9\r\n\
+     Cookie: session=8f14e45fceea167a5a36dedd4bea2543; theme=dark\r\n\
+     \r\n";
+
+const ITERATIONS: usize = 200_000;
+
+fn main() {
+    black_box(HttpRequest::new(BROWSER_REQUEST).unwrap());
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0 .. ITERATIONS {
+        black_box(HttpRequest::new(black_box(BROWSER_REQUEST)).unwrap());
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!("parse browser request: {} ns/iter, {} allocations/iter",
+             elapsed.as_nanos() / ITERATIONS as u128,
+             allocations / ITERATIONS);
+}
@@ -0,0 +1,248 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::string::ToString;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum HttpParseError {
+    MissingRequestLine,
+    MalformedHeader(String),
+    MissingHeaderColon(String),
+    InvalidMethod { token: String, index: usize },
+    MissingUri,
+    InvalidUri { token: String, index: usize },
+    MalformedRequestLine(String),
+    MalformedStatusLine(String),
+    InvalidStatusCode(String),
+    NonAsciiByte(usize),
+    InvalidUtf8(usize),
+    InvalidPercentEncoding(String),
+    InvalidContentLength(String),
+    IncompleteBody { expected: usize, available: usize },
+    MalformedChunkSize(String),
+    MalformedChunk,
+    IncompleteChunk,
+    ForbiddenTrailer(String),
+    InvalidBase64(String),
+    MalformedCredentials,
+    MalformedRange(String),
+    InvalidHeaderName(String),
+    MissingPseudoHeader(String),
+    InvalidPseudoHeader(String),
+    InvalidFieldValue(String),
+    BareLineFeed(usize),
+    ContentLengthWithTransferEncoding,
+    ChunkedNotFinal,
+    ConflictingContentLength,
+    MissingHost,
+    DuplicateHost,
+    TooManyHeaders,
+    HeaderLineTooLong,
+    HeaderBlockTooLarge,
+    ParserFinished,
+    #[cfg(feature = "std")]
+    Io(io::ErrorKind)
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for HttpParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HttpParseError::MissingRequestLine =>
+                write!(f, "Missing request line"),
+            HttpParseError::MalformedHeader(ref line) =>
+                write!(f, "Malformed header: {}", line),
+            HttpParseError::MissingHeaderColon(ref line) =>
+                write!(f, "Header line has no colon: {}", line),
+            HttpParseError::InvalidMethod { ref token, index } =>
+                write!(f, "Invalid method {:?} at index {}", token, index),
+            HttpParseError::MissingUri =>
+                write!(f, "Missing uri in request line"),
+            HttpParseError::InvalidUri { ref token, index } =>
+                write!(f, "Invalid uri {:?} at index {}", token, index),
+            HttpParseError::MalformedRequestLine(ref line) =>
+                write!(f, "Malformed request line: {}", line),
+            HttpParseError::MalformedStatusLine(ref line) =>
+                write!(f, "Malformed status line: {}", line),
+            HttpParseError::InvalidStatusCode(ref code) =>
+                write!(f, "Invalid status code: {}", code),
+            HttpParseError::NonAsciiByte(offset) =>
+                write!(f, "Non-ASCII byte at offset {}", offset),
+            HttpParseError::InvalidUtf8(offset) =>
+                write!(f, "Invalid UTF-8 at offset {}", offset),
+            HttpParseError::InvalidPercentEncoding(ref input) =>
+                write!(f, "Invalid percent-encoding: {}", input),
+            HttpParseError::InvalidContentLength(ref length) =>
+                write!(f, "Invalid Content-Length: {}", length),
+            HttpParseError::IncompleteBody { expected, available } =>
+                write!(f, "Expected {} body bytes but only {} available",
+                       expected, available),
+            HttpParseError::MalformedChunkSize(ref line) =>
+                write!(f, "Malformed chunk size: {}", line),
+            HttpParseError::MalformedChunk =>
+                write!(f, "Chunk data not terminated by CRLF"),
+            HttpParseError::IncompleteChunk =>
+                write!(f, "Incomplete chunked body"),
+            HttpParseError::ForbiddenTrailer(ref name) =>
+                write!(f, "Header not allowed in trailers: {}", name),
+            HttpParseError::InvalidBase64(ref input) =>
+                write!(f, "Invalid base64: {}", input),
+            HttpParseError::MalformedCredentials =>
+                write!(f, "Credentials are not of the form user:password"),
+            HttpParseError::MalformedRange(ref range) =>
+                write!(f, "Malformed range: {}", range),
+            HttpParseError::InvalidHeaderName(ref name) =>
+                write!(f, "Invalid header name: {:?}", name),
+            HttpParseError::MissingPseudoHeader(ref name) =>
+                write!(f, "Missing pseudo-header {}", name),
+            HttpParseError::InvalidPseudoHeader(ref name) =>
+                write!(f, "Unexpected pseudo-header {}", name),
+            HttpParseError::InvalidFieldValue(ref name) =>
+                write!(f, "Invalid characters in the value of {}", name),
+            HttpParseError::BareLineFeed(offset) =>
+                write!(f, "Line feed without carriage return at offset {}",
+                       offset),
+            HttpParseError::ContentLengthWithTransferEncoding =>
+                write!(f, "Both Content-Length and Transfer-Encoding present"),
+            HttpParseError::ChunkedNotFinal =>
+                write!(f, "Chunked is not the final transfer coding"),
+            HttpParseError::ConflictingContentLength =>
+                write!(f, "Conflicting Content-Length values"),
+            HttpParseError::MissingHost =>
+                write!(f, "Missing Host header"),
+            HttpParseError::DuplicateHost =>
+                write!(f, "More than one Host header"),
+            HttpParseError::TooManyHeaders =>
+                write!(f, "Too many headers"),
+            HttpParseError::HeaderLineTooLong =>
+                write!(f, "Header line too long"),
+            HttpParseError::HeaderBlockTooLarge =>
+                write!(f, "Header block too large"),
+            HttpParseError::ParserFinished =>
+                write!(f, "Request parser has already returned a result"),
+            #[cfg(feature = "std")]
+            HttpParseError::Io(kind) =>
+                write!(f, "I/O error: {:?}", kind)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for HttpParseError {}
+
+// The errors of the request and header line syntax, which HttpRequestRef
+// reports as is. Rather than a copy of the offending text they carry its byte
+// offset into the input, so reporting them needs no allocator.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub enum HttpSyntaxError {
+    MissingRequestLine,
+    MalformedRequestLine(usize),
+    MissingUri,
+    InvalidMethod(usize),
+    InvalidUri(usize),
+    BareLineFeed(usize),
+    MalformedHeader(usize),
+    MissingHeaderColon(usize),
+    InvalidHeaderName(usize)
+}
+
+impl HttpSyntaxError {
+    // The same error for offsets counted from `by` bytes further back.
+    pub(crate) fn shifted(self, by: usize) -> HttpSyntaxError {
+        match self {
+            HttpSyntaxError::MalformedRequestLine(offset) =>
+                HttpSyntaxError::MalformedRequestLine(offset + by),
+            HttpSyntaxError::InvalidMethod(offset) =>
+                HttpSyntaxError::InvalidMethod(offset + by),
+            HttpSyntaxError::InvalidUri(offset) =>
+                HttpSyntaxError::InvalidUri(offset + by),
+            HttpSyntaxError::BareLineFeed(offset) =>
+                HttpSyntaxError::BareLineFeed(offset + by),
+            HttpSyntaxError::MalformedHeader(offset) =>
+                HttpSyntaxError::MalformedHeader(offset + by),
+            HttpSyntaxError::MissingHeaderColon(offset) =>
+                HttpSyntaxError::MissingHeaderColon(offset + by),
+            HttpSyntaxError::InvalidHeaderName(offset) =>
+                HttpSyntaxError::InvalidHeaderName(offset + by),
+            error => error
+        }
+    }
+
+    // The owned parsers' error, quoting `input`, the text the offset points
+    // into. A line error quotes everything from the offset on, so callers
+    // pass the single line they parsed; a token error quotes the token.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn into_parse_error(self, input: &str) -> HttpParseError {
+        let token = |offset: usize| {
+            input[offset ..].split(char::is_whitespace)
+                            .next()
+                            .unwrap_or("")
+                            .to_string()
+        };
+        match self {
+            HttpSyntaxError::MissingRequestLine =>
+                HttpParseError::MissingRequestLine,
+            HttpSyntaxError::MalformedRequestLine(offset) =>
+                HttpParseError::MalformedRequestLine(
+                    input[offset ..].to_string()),
+            HttpSyntaxError::MissingUri =>
+                HttpParseError::MissingUri,
+            HttpSyntaxError::InvalidMethod(index) =>
+                HttpParseError::InvalidMethod { token: token(index), index },
+            HttpSyntaxError::InvalidUri(index) =>
+                HttpParseError::InvalidUri { token: token(index), index },
+            HttpSyntaxError::BareLineFeed(offset) =>
+                HttpParseError::BareLineFeed(offset),
+            HttpSyntaxError::MalformedHeader(offset) =>
+                HttpParseError::MalformedHeader(input[offset ..].to_string()),
+            HttpSyntaxError::MissingHeaderColon(offset) =>
+                HttpParseError::MissingHeaderColon(
+                    input[offset ..].to_string()),
+            HttpSyntaxError::InvalidHeaderName(offset) => {
+                let line = &input[offset ..];
+                let name = line.find(':').map_or(line, |i| &line[.. i]);
+                HttpParseError::InvalidHeaderName(name.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for HttpSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HttpSyntaxError::MissingRequestLine =>
+                write!(f, "Missing request line"),
+            HttpSyntaxError::MalformedRequestLine(offset) =>
+                write!(f, "Malformed request line at offset {}", offset),
+            HttpSyntaxError::MissingUri =>
+                write!(f, "Missing uri in request line"),
+            HttpSyntaxError::InvalidMethod(offset) =>
+                write!(f, "Invalid method at offset {}", offset),
+            HttpSyntaxError::InvalidUri(offset) =>
+                write!(f, "Invalid uri at offset {}", offset),
+            HttpSyntaxError::BareLineFeed(offset) =>
+                write!(f, "Line feed without carriage return at offset {}",
+                       offset),
+            HttpSyntaxError::MalformedHeader(offset) =>
+                write!(f, "Malformed header at offset {}", offset),
+            HttpSyntaxError::MissingHeaderColon(offset) =>
+                write!(f, "Header line has no colon at offset {}", offset),
+            HttpSyntaxError::InvalidHeaderName(offset) =>
+                write!(f, "Invalid header name at offset {}", offset)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for HttpSyntaxError {}
@@ -0,0 +1,277 @@
+use alloc::string::String;
+use alloc::string::ToString;
+use core::fmt;
+use core::str::FromStr;
+
+use error::HttpParseError;
+use header::HttpHeaderSplitTrim;
+use syntax::is_token;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(PartialOrd)]
+#[derive(Ord)]
+#[derive(Hash)]
+pub enum HttpMethod {
+    Options,
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Trace,
+    Connect,
+    Patch,
+    // Extensions sort after every known method, by name. Keep new variants
+    // above this one.
+    Extension(String)
+}
+
+const KNOWN_METHODS: [(&str, HttpMethod); 9] = [
+    ("options", HttpMethod::Options),
+    ("get",     HttpMethod::Get),
+    ("head",    HttpMethod::Head),
+    ("post",    HttpMethod::Post),
+    ("put",     HttpMethod::Put),
+    ("delete",  HttpMethod::Delete),
+    ("trace",   HttpMethod::Trace),
+    ("connect", HttpMethod::Connect),
+    ("patch",   HttpMethod::Patch)
+];
+
+impl FromStr for HttpMethod {
+    type Err = HttpParseError;
+
+    fn from_str(method: &str) -> Result<HttpMethod, HttpParseError> {
+        if !is_token(method) {
+            return Err(HttpParseError::InvalidMethod {
+                token: method.to_string(),
+                index: 0
+            })
+        }
+        for known in KNOWN_METHODS.iter() {
+            if known.0.eq_ignore_ascii_case(method) {
+                return Ok(known.1.clone())
+            }
+        }
+        Ok(HttpMethod::Extension(method.to_string()))
+    }
+}
+
+// Classification per RFC 7231 section 4. Extension methods are unknown, so
+// they are treated as neither safe nor idempotent but free to carry a body.
+impl HttpMethod {
+    // GET, HEAD, DELETE, CONNECT and TRACE define no meaning for a body:
+    // servers may reject one and intermediaries may drop it.
+    pub fn allows_body(&self) -> bool {
+        matches!(*self, HttpMethod::Post |
+                        HttpMethod::Put |
+                        HttpMethod::Patch |
+                        HttpMethod::Options |
+                        HttpMethod::Extension(_))
+    }
+
+    pub fn is_safe(&self) -> bool {
+        matches!(*self, HttpMethod::Get |
+                        HttpMethod::Head |
+                        HttpMethod::Options |
+                        HttpMethod::Trace)
+    }
+
+    pub fn is_idempotent(&self) -> bool {
+        self.is_safe() ||
+            matches!(*self, HttpMethod::Put | HttpMethod::Delete)
+    }
+}
+
+impl HttpMethod {
+    pub(crate) fn as_str(&self) -> &str {
+        match *self {
+            HttpMethod::Options          => "OPTIONS",
+            HttpMethod::Get              => "GET",
+            HttpMethod::Head             => "HEAD",
+            HttpMethod::Post             => "POST",
+            HttpMethod::Put              => "PUT",
+            HttpMethod::Delete           => "DELETE",
+            HttpMethod::Trace            => "TRACE",
+            HttpMethod::Connect          => "CONNECT",
+            HttpMethod::Patch            => "PATCH",
+            HttpMethod::Extension(ref x) => x
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub enum HttpVersion {
+    Http09,
+    Http10,
+    Http11,
+    Http20,
+    Other(String)
+}
+
+impl HttpVersion {
+    pub fn new(version: &str) -> HttpVersion {
+        // The common spellings, without splitting.
+        match version {
+            "HTTP/1.1" => return HttpVersion::Http11,
+            "HTTP/1.0" => return HttpVersion::Http10,
+            _          => {}
+        }
+        let parts = version.splitn_trim(2, "/");
+        if parts.len() == 2 && parts[0] == "HTTP" {
+            match parts[1].split_trim(".").as_slice() {
+                ["0", "9"]         => return HttpVersion::Http09,
+                ["1", "0"]         => return HttpVersion::Http10,
+                ["1", "1"]         => return HttpVersion::Http11,
+                ["2", "0"] | ["2"] => return HttpVersion::Http20,
+                _                  => {}
+            }
+        }
+        HttpVersion::Other(version.to_string())
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match *self {
+            HttpVersion::Http09       => "HTTP/0.9",
+            HttpVersion::Http10       => "HTTP/1.0",
+            HttpVersion::Http11       => "HTTP/1.1",
+            HttpVersion::Http20       => "HTTP/2.0",
+            HttpVersion::Other(ref x) => x
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use error::HttpParseError;
+    use super::HttpMethod;
+    use super::HttpVersion;
+    use header::HttpHeaderName;
+
+    #[test]
+    fn http_version_parse() {
+        assert_eq!(HttpVersion::Http09, HttpVersion::new("HTTP/0.9"));
+        assert_eq!(HttpVersion::Http10, HttpVersion::new("HTTP/1.0"));
+        assert_eq!(HttpVersion::Http11, HttpVersion::new("HTTP/1.1"));
+        assert_eq!(HttpVersion::Http20, HttpVersion::new("HTTP/2.0"));
+        assert_eq!(HttpVersion::Http20, HttpVersion::new("HTTP/2"));
+        assert_eq!(HttpVersion::Other("HTTP/3.7".to_string()),
+                   HttpVersion::new("HTTP/3.7"));
+        assert_eq!(HttpVersion::Other("SPDY/1.1".to_string()),
+                   HttpVersion::new("SPDY/1.1"));
+        assert_eq!(HttpVersion::Other("HTTP1.1".to_string()),
+                   HttpVersion::new("HTTP1.1"));
+    }
+
+    #[test]
+    fn http_display_names() {
+        assert_eq!("CONNECT", HttpMethod::Connect.to_string());
+        assert_eq!("Accept-Encoding",
+                   HttpHeaderName::AcceptEncoding.to_string());
+        assert_eq!("HTTP/1.1", HttpVersion::Http11.to_string());
+    }
+
+    #[test]
+    fn http_method_from_str() {
+        assert_eq!(Ok(HttpMethod::Get), "GET".parse::<HttpMethod>());
+        assert_eq!(Ok(HttpMethod::Delete), "delete".parse::<HttpMethod>());
+        assert_eq!(Ok(HttpMethod::Options), "Options".parse::<HttpMethod>());
+        assert_eq!(Ok(HttpMethod::Extension("PURGE".to_string())),
+                   "PURGE".parse::<HttpMethod>());
+        assert_eq!(Err(HttpParseError::InvalidMethod {
+                       token: "G(E)T".to_string(),
+                       index: 0
+                   }),
+                   "G(E)T".parse::<HttpMethod>());
+        assert!("".parse::<HttpMethod>().is_err());
+    }
+
+    #[test]
+    fn http_method_ascii_case_matching() {
+        for method in &["PATCH", "patch", "Patch", "pAtCh"] {
+            assert_eq!(Ok(HttpMethod::Patch), method.parse::<HttpMethod>());
+        }
+        // U+212A (Kelvin sign) is not a token character, and the dotless i
+        // must not fold to an ASCII 'i' either.
+        assert!("\u{212a}ILL".parse::<HttpMethod>().is_err());
+        assert!("OPT\u{131}ONS".parse::<HttpMethod>().is_err());
+        assert_eq!(Ok(HttpMethod::Extension("Get2".to_string())),
+                   "Get2".parse::<HttpMethod>());
+    }
+
+    #[test]
+    fn http_method_classification() {
+        fn classify(method: HttpMethod) -> (bool, bool, bool) {
+            (method.is_safe(), method.is_idempotent(), method.allows_body())
+        }
+        assert_eq!((true,  true,  false), classify(HttpMethod::Get));
+        assert_eq!((true,  true,  false), classify(HttpMethod::Head));
+        assert_eq!((true,  true,  true),  classify(HttpMethod::Options));
+        assert_eq!((true,  true,  false), classify(HttpMethod::Trace));
+        assert_eq!((false, true,  true),  classify(HttpMethod::Put));
+        assert_eq!((false, true,  false), classify(HttpMethod::Delete));
+        assert_eq!((false, false, true),  classify(HttpMethod::Post));
+        assert_eq!((false, false, true),  classify(HttpMethod::Patch));
+        assert_eq!((false, false, false), classify(HttpMethod::Connect));
+        assert_eq!((false, false, true),
+                   classify(HttpMethod::Extension("PROPFIND".to_string())));
+    }
+
+    #[test]
+    fn http_method_display() {
+        assert_eq!("OPTIONS", HttpMethod::Options.to_string());
+        assert_eq!("GET", HttpMethod::Get.to_string());
+        assert_eq!("HEAD", HttpMethod::Head.to_string());
+        assert_eq!("POST", HttpMethod::Post.to_string());
+        assert_eq!("PUT", HttpMethod::Put.to_string());
+        assert_eq!("DELETE", HttpMethod::Delete.to_string());
+        assert_eq!("TRACE", HttpMethod::Trace.to_string());
+        assert_eq!("CONNECT", HttpMethod::Connect.to_string());
+        assert_eq!("PATCH", HttpMethod::Patch.to_string());
+        assert_eq!("PROPFIND",
+                   HttpMethod::Extension("PROPFIND".to_string()).to_string());
+        assert_eq!("GET", "get".parse::<HttpMethod>().unwrap().to_string());
+        assert_eq!("M-SEARCH",
+                   "M-SEARCH".parse::<HttpMethod>().unwrap().to_string());
+    }
+
+    #[test]
+    fn http_method_ord() {
+        let mut methods = vec![
+            HttpMethod::Extension("PURGE".to_string()),
+            HttpMethod::Patch,
+            HttpMethod::Get,
+            HttpMethod::Extension("LINK".to_string()),
+            HttpMethod::Options
+        ];
+        methods.sort();
+        assert_eq!(vec![HttpMethod::Options,
+                        HttpMethod::Get,
+                        HttpMethod::Patch,
+                        HttpMethod::Extension("LINK".to_string()),
+                        HttpMethod::Extension("PURGE".to_string())],
+                   methods);
+        assert!(HttpMethod::Connect < HttpMethod::Extension("A".to_string()));
+    }
+}
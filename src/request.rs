@@ -0,0 +1,3461 @@
+use core::result::Result;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::slice;
+use core::str;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::net::IpAddr;
+
+use error::HttpParseError;
+#[cfg(feature = "std")]
+use header::HeaderMap;
+use header::HttpHeader;
+use header::HttpHeaderName;
+use header::HttpHeaderSplitTrim;
+use header::parse_headers;
+use header::parse_list;
+use header::parse_weighted_list;
+use header::split_unquoted;
+use header::unquote;
+use method::HttpMethod;
+use method::HttpVersion;
+use request_ref::HttpRequestRef;
+use syntax::DEFAULT_MAX_LEADING_EMPTY_LINES;
+use syntax::check_line_endings;
+use syntax::check_uri;
+use syntax::find;
+use syntax::has_scheme;
+use syntax::head_len;
+use syntax::is_scheme;
+use syntax::is_token;
+use syntax::leading_empty_lines_len;
+use syntax::skip_empty_lines;
+use syntax::split_request_line;
+
+// Headers that only apply to a single connection and must not be forwarded
+// by a proxy (RFC 7230 section 6.1).
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "Connection",
+    "Keep-Alive",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding",
+    "Upgrade"
+];
+
+// The connection-specific headers an HTTP/2 request must not carry (RFC 7540
+// 8.1.2.2), besides the proxy credentials meant for this hop. TE is allowed
+// when its value is just "trailers".
+const HTTP2_EXCLUDED_HEADERS: [&str; 8] = [
+    "Connection",
+    "Keep-Alive",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "Proxy-Connection",
+    "TE",
+    "Transfer-Encoding",
+    "Upgrade"
+];
+
+// Headers a sender must not put in a chunked trailer, as recipients would
+// have needed them before the body to frame, route or authorize the message
+// (RFC 7230 section 4.1.2).
+const FORBIDDEN_TRAILERS: [&str; 14] = [
+    "Authorization",
+    "Cache-Control",
+    "Content-Encoding",
+    "Content-Length",
+    "Content-Range",
+    "Content-Type",
+    "Expect",
+    "Host",
+    "Max-Forwards",
+    "Pragma",
+    "Range",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding"
+];
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub struct HttpRequest {
+    pub(crate) method:  HttpMethod,
+    pub(crate) uri:     String,
+    pub(crate) version: HttpVersion,
+    pub(crate) headers: Vec<HttpHeader>,
+    pub(crate) body:    Vec<u8>
+}
+
+impl HttpRequest {
+    pub fn new(header: &str) -> Result<HttpRequest, HttpParseError> {
+        HttpRequest::parse(header, false, None)
+    }
+
+    pub fn new_with_limits(
+        header: &str,
+        limits: &ParseLimits
+    ) -> Result<HttpRequest, HttpParseError> {
+        HttpRequest::parse(header, false, Some(limits))
+    }
+
+    pub fn new_lenient(header: &str) -> Result<HttpRequest, HttpParseError> {
+        HttpRequest::parse(header, true, None)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<HttpRequest, HttpParseError> {
+        // The empty lines allowed before the request line aren't the end of
+        // the head; `new` skips them.
+        let skip = leading_empty_lines_len(bytes, false);
+        HttpRequest::new(head_str(&bytes[.. skip + head_len(&bytes[skip ..])])?)
+    }
+
+    // The body is the Content-Length bytes after the head. A chunked body is
+    // kept encoded, as it arrived, up to and including its trailers;
+    // `decode_chunked` decodes it.
+    pub fn from_bytes_with_body(
+        bytes: &[u8]
+    ) -> Result<HttpRequest, HttpParseError> {
+        let skip = leading_empty_lines_len(bytes, false);
+        let head_len = skip + head_len(&bytes[skip ..]);
+        let mut request = HttpRequest::from_bytes(&bytes[.. head_len])?;
+        let length = request.content_length()?;
+        // A message carrying both headers can be framed differently by each
+        // hop that reads it, RFC 7230 3.3.3 requires treating it as an error.
+        if length.is_some() &&
+           request.header(&HttpHeaderName::TransferEncoding).is_some() {
+            return Err(HttpParseError::ContentLengthWithTransferEncoding)
+        }
+        request.transfer_codings()?;
+        // Without chunked as the final coding the body only ends when the
+        // connection closes, which can't hold for a request (RFC 7230 3.3.3).
+        if request.header(&HttpHeaderName::TransferEncoding).is_some() &&
+           !request.is_chunked() {
+            return Err(HttpParseError::ChunkedNotFinal)
+        }
+        let length = if request.is_chunked() {
+            parse_chunked(&bytes[head_len ..])?.1
+        } else {
+            length.unwrap_or(0)
+        };
+        let available = bytes.len() - head_len;
+        if length > available {
+            return Err(HttpParseError::IncompleteBody {
+                expected: length,
+                available
+            })
+        }
+        request.body = bytes[head_len .. head_len + length].to_vec();
+        Ok(request)
+    }
+
+    // Reads a byte at a time so nothing past the header block is consumed and
+    // the body can still be read from `r`. Passing a BufReader avoids a read
+    // call per byte, as long as the body is then read from the same BufReader.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: io::Read>(
+        r: &mut R
+    ) -> Result<HttpRequest, HttpParseError> {
+        let mut parser = RequestParser::new();
+        let mut byte = [0u8; 1];
+        loop {
+            match r.read(&mut byte) {
+                Ok(0) => return Err(
+                    HttpParseError::Io(io::ErrorKind::UnexpectedEof)),
+                Ok(_) => match parser.feed(&byte) {
+                    ParseProgress::NeedMore          => {},
+                    ParseProgress::Complete(request) => return Ok(request),
+                    ParseProgress::Error(e)          => return Err(e)
+                },
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(HttpParseError::Io(e.kind()))
+            }
+        }
+    }
+
+    // Rebuilds an HTTP/1.1 request from HTTP/2 style pairs: the :method,
+    // :scheme, :path and :authority pseudo-headers followed by the regular
+    // headers. CONNECT carries only :method and :authority. The authority
+    // becomes the Host header unless the regular headers include one.
+    pub fn from_pseudo_headers(
+        pairs: &[(String, String)]
+    ) -> Result<HttpRequest, HttpParseError> {
+        let mut method = None;
+        let mut scheme = None;
+        let mut authority = None;
+        let mut path = None;
+        let mut headers = Vec::new();
+        for pair in pairs {
+            let (name, value) = (pair.0.as_str(), pair.1.as_str());
+            let invalid_value = || {
+                HttpParseError::InvalidFieldValue(name.to_string())
+            };
+            // HTTP/2 values are length-delimited, so they can hold a CR or LF
+            // that would start a new header, or a new request, once written
+            // as HTTP/1.1 (RFC 9113 8.2.1).
+            if value.contains(['\r', '\n', '\0']) {
+                return Err(invalid_value())
+            }
+            if !name.starts_with(':') {
+                if !is_token(name) {
+                    return Err(HttpParseError::InvalidHeaderName(
+                        name.to_string()))
+                }
+                headers.push(HttpHeader {
+                    name:          HttpHeaderName::new(name),
+                    original_name: name.to_string(),
+                    value:         value.to_string()
+                });
+                continue
+            }
+            let invalid = || {
+                HttpParseError::InvalidPseudoHeader(name.to_string())
+            };
+            let slot = match name {
+                ":method"    => &mut method,
+                ":scheme"    => &mut scheme,
+                ":authority" => &mut authority,
+                ":path"      => &mut path,
+                _            => return Err(invalid())
+            };
+            // Pseudo-headers come before the regular ones, once each.
+            if slot.is_some() || !headers.is_empty() {
+                return Err(invalid())
+            }
+            // These end up in the request line, where whitespace separates
+            // its parts.
+            if value.contains(|c: char| c.is_whitespace() || c.is_control()) {
+                return Err(invalid_value())
+            }
+            *slot = Some(value);
+        }
+        let missing = |name: &str| {
+            HttpParseError::MissingPseudoHeader(name.to_string())
+        };
+        let method = method.ok_or_else(|| missing(":method"))?
+                           .parse::<HttpMethod>()?;
+        let uri = if method == HttpMethod::Connect {
+            authority.ok_or_else(|| missing(":authority"))?
+        } else {
+            scheme.ok_or_else(|| missing(":scheme"))?;
+            path.ok_or_else(|| missing(":path"))?
+        };
+        check_uri(&method.to_string(), uri, 0).map_err(|_| {
+            HttpParseError::InvalidUri { token: uri.to_string(), index: 0 }
+        })?;
+        if let Some(authority) = authority {
+            if !headers.iter().any(|h| h.name == HttpHeaderName::Host) {
+                headers.insert(0, HttpHeader {
+                    name:          HttpHeaderName::Host,
+                    original_name: HttpHeaderName::Host.to_string(),
+                    value:         authority.to_string()
+                });
+            }
+        }
+
+        Ok(HttpRequest {
+            method,
+            uri: uri.to_string(),
+            version: HttpVersion::Http11,
+            headers,
+            body: Vec::new()
+        })
+    }
+
+    fn content_length(&self) -> Result<Option<usize>, HttpParseError> {
+        let mut length = None;
+        for value in self.header_values(&HttpHeaderName::ContentLength) {
+            // A header without any length can't frame the body.
+            let values = parse_list(value);
+            if values.is_empty() {
+                return Err(HttpParseError::InvalidContentLength(
+                    value.to_string()))
+            }
+            for value in values {
+                let invalid = || {
+                    HttpParseError::InvalidContentLength(value.to_string())
+                };
+                if !value.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid())
+                }
+                let parsed = value.parse::<usize>().map_err(|_| invalid())?;
+                match length {
+                    Some(length) if length != parsed =>
+                        return Err(HttpParseError::ConflictingContentLength),
+                    _ => length = Some(parsed)
+                }
+            }
+        }
+        Ok(length)
+    }
+
+    fn parse(
+        header:  &str,
+        lenient: bool,
+        limits:  Option<&ParseLimits>
+    ) -> Result<HttpRequest, HttpParseError> {
+        if header.trim().is_empty() {
+            return Result::Err(HttpParseError::MissingRequestLine)
+        }
+        let max_empty_lines = limits.map_or(
+            ParseLimits::default().max_leading_empty_lines,
+            |limits| limits.max_leading_empty_lines);
+        let skip = skip_empty_lines(header.as_bytes(), max_empty_lines,
+                                    lenient);
+        let header = &header[skip ..];
+        let lines: Vec<&str> = if lenient {
+            header.split('\n')
+                  .map(|l| l.strip_suffix('\r').unwrap_or(l))
+                  .collect()
+        } else {
+            check_line_endings(header)
+                .map_err(|e| e.into_parse_error(header))?;
+            header.split("\r\n").collect()
+        };
+        if let Some(limits) = limits {
+            check_limits(&lines, limits)?;
+        }
+        let (method, uri, version) = request_line(lines[0], lenient)?;
+        let headers = parse_headers(&lines[1 ..], lenient)?;
+        // RFC 7230 5.4: an HTTP/1.1 request carries exactly one Host header.
+        // Proxies disagreeing on which of several applies is a smuggling
+        // vector, so strict parsing rejects both cases.
+        if !lenient && version == HttpVersion::Http11 {
+            match headers.iter()
+                         .filter(|h| h.name == HttpHeaderName::Host)
+                         .count() {
+                0 => return Err(HttpParseError::MissingHost),
+                1 => {}
+                _ => return Err(HttpParseError::DuplicateHost)
+            }
+        }
+
+        Result::Ok(HttpRequest {
+            method,
+            uri,
+            version,
+            headers,
+            body: Vec::new()
+        })
+    }
+
+    pub fn method(&self) -> &HttpMethod {
+        &self.method
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn version(&self) -> &HttpVersion {
+        &self.version
+    }
+
+    pub fn headers(&self) -> &[HttpHeader] {
+        &self.headers
+    }
+
+    pub fn headers_iter(&self) -> slice::Iter<'_, HttpHeader> {
+        self.headers.iter()
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn with_uri(&self, new_uri: impl Into<String>) -> HttpRequest {
+        HttpRequest { uri: new_uri.into(), ..self.clone() }
+    }
+
+    pub fn add_header(&mut self, name: HttpHeaderName, value: &str) {
+        let original_name = name.to_string();
+        self.headers.push(HttpHeader {
+            name,
+            original_name,
+            value: value.to_string()
+        });
+    }
+
+    pub fn remove_header(&mut self, name: &HttpHeaderName) {
+        self.headers.retain(|h| h.name != *name);
+    }
+
+    // Replaces the value of the first header with the given name, keeping its
+    // position and casing, and drops any later ones.
+    pub fn set_header(&mut self, name: HttpHeaderName, value: &str) {
+        match self.headers.iter().position(|h| h.name == name) {
+            Some(i) => {
+                self.headers[i].value = value.to_string();
+                let rest = self.headers.split_off(i + 1);
+                self.headers.extend(
+                    rest.into_iter().filter(|h| h.name != name));
+            },
+            None    => self.add_header(name, value)
+        }
+    }
+
+    // Also removes every header the Connection header lists by name, other
+    // than Host and Content-Length. Without Transfer-Encoding the next hop
+    // can't find the end of a chunked body, so the body is decoded and framed
+    // by Content-Length instead. A body that can't be decoded here, like one
+    // still to be streamed after the head, keeps its Transfer-Encoding.
+    pub fn strip_hop_by_hop(&mut self) {
+        let mut names = self.hop_by_hop_names();
+        if self.header(&HttpHeaderName::TransferEncoding).is_some() &&
+           !self.dechunk_body() {
+            names.retain(|name| *name != HttpHeaderName::TransferEncoding);
+        }
+        self.headers.retain(|h| !names.contains(&h.name));
+    }
+
+    // Replaces a body sent with only the chunked coding by its decoded bytes
+    // and a matching Content-Length, returning whether it could.
+    fn dechunk_body(&mut self) -> bool {
+        let chunked = self.transfer_codings()
+                          .is_ok_and(|codings| codings == ["chunked"]);
+        let body = match decode_chunked(&self.body) {
+            Ok(body) if chunked => body,
+            _                   => return false
+        };
+        self.set_header(HttpHeaderName::ContentLength, &body.len().to_string());
+        self.body = body;
+        true
+    }
+
+    fn hop_by_hop_names(&self) -> Vec<HttpHeaderName> {
+        self.connection_specific_names(&HOP_BY_HOP_HEADERS)
+    }
+
+    // The given header names and those the Connection header lists.
+    fn connection_specific_names(
+        &self,
+        fixed: &[&str]
+    ) -> Vec<HttpHeaderName> {
+        let mut names: Vec<HttpHeaderName> = fixed
+            .iter()
+            .map(|name| HttpHeaderName::new(name))
+            .collect();
+        for value in self.header_values(&HttpHeaderName::Connection) {
+            for option in parse_list(value) {
+                // Connection can't make the headers that route and frame the
+                // message hop-by-hop, or stripping it would let the next hop
+                // read the body as another request.
+                match HttpHeaderName::new(option) {
+                    HttpHeaderName::Host | HttpHeaderName::ContentLength => {}
+                    name => names.push(name)
+                }
+            }
+        }
+        names
+    }
+
+    // The inverse of `from_pseudo_headers`. The authority and scheme come from
+    // an absolute-form target, otherwise from the Host header and "http".
+    // Host and the connection-specific headers have no place in an HTTP/2
+    // request; TE is kept only as "TE: trailers", which gRPC relies on.
+    pub fn to_pseudo_headers(&self) -> Vec<(String, String)> {
+        let pair = |name: &str, value: &str| {
+            (name.to_string(), value.to_string())
+        };
+        let mut pairs = vec![pair(":method", &self.method.to_string())];
+        if self.method == HttpMethod::Connect {
+            pairs.push(pair(":authority", &self.uri));
+        } else {
+            let absolute = split_absolute_uri(&self.uri);
+            let (scheme, authority, path) = match (absolute,
+                                                   self.absolute_uri()) {
+                (Some(parts), Some((authority, path))) =>
+                    (parts.0, Some(authority), path),
+                _ => {
+                    let host = self.header(&HttpHeaderName::Host)
+                                   .map(|h| h.value.clone());
+                    ("http", host, self.uri.clone())
+                }
+            };
+            pairs.push(pair(":path", &path));
+            if let Some(authority) = authority {
+                pairs.push(pair(":authority", &authority));
+            }
+            pairs.push(pair(":scheme", &scheme.to_ascii_lowercase()));
+        }
+        let excluded = self.connection_specific_names(&HTTP2_EXCLUDED_HEADERS);
+        let te = HttpHeaderName::new("TE");
+        for header in &self.headers {
+            let trailers = header.name == te &&
+                           header.value.trim().eq_ignore_ascii_case("trailers");
+            if trailers {
+                pairs.push(pair("te", "trailers"));
+            } else if header.name != HttpHeaderName::Host &&
+                      !excluded.contains(&header.name) {
+                pairs.push((header.original_name.to_ascii_lowercase(),
+                            header.value.clone()));
+            }
+        }
+        pairs
+    }
+
+    // Appends to the last X-Forwarded-For header so the addresses stay in
+    // the order the proxies saw them.
+    #[cfg(feature = "std")]
+    pub fn append_forwarded_for(&mut self, addr: IpAddr) {
+        let name = HttpHeaderName::Custom("X-Forwarded-For".to_string());
+        match self.headers.iter_mut().rev().find(|h| h.name == name) {
+            Some(header) => {
+                if !header.value.is_empty() {
+                    header.value.push_str(", ");
+                }
+                header.value.push_str(&addr.to_string());
+            },
+            None         => self.add_header(name, &addr.to_string())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn header_map(&self) -> HeaderMap {
+        HeaderMap::from_headers(&self.headers)
+    }
+
+    /// Returns the first header with the given name. Use `header_values` when
+    /// every occurrence of a repeated header is significant.
+    pub fn header(&self, name: &HttpHeaderName) -> Option<&HttpHeader> {
+        self.headers.iter().find(|h| h.name == *name)
+    }
+
+    /// Returns the values of all headers with the given name, in the order
+    /// they appeared in the request.
+    pub fn header_values(&self, name: &HttpHeaderName) -> Vec<&str> {
+        self.headers.iter()
+            .filter(|h| h.name == *name)
+            .map(|h| h.value())
+            .collect()
+    }
+
+    pub fn combined_header(&self, name: &HttpHeaderName) -> Option<String> {
+        let values = self.header_values(name);
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(", "))
+        }
+    }
+
+    pub fn header_str(&self, name: &str) -> Option<&str> {
+        self.header(&HttpHeaderName::new(name)).map(|h| h.value())
+    }
+
+    pub fn path(&self) -> &str {
+        match self.uri.find('?') {
+            Some(i) => &self.uri[.. i],
+            None    => &self.uri
+        }
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.uri.find('?').map(|i| &self.uri[i + 1 ..])
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_to_with(w, &SerializeOptions::default())
+    }
+
+    // Writes the same bytes as `to_bytes`, body included, without building
+    // them in memory first.
+    #[cfg(feature = "std")]
+    pub fn write_to_with<W: io::Write>(&self, w: &mut W,
+                                       options: &SerializeOptions)
+                                       -> io::Result<()> {
+        write!(w, "{} {} {}\r\n", self.method, self.uri, self.version)?;
+        for header in &self.headers {
+            if options.lowercase_header_names {
+                let name = header.original_name.to_ascii_lowercase();
+                w.write_all(name.as_bytes())?;
+            } else {
+                w.write_all(header.original_name.as_bytes())?;
+            }
+            w.write_all(b": ")?;
+            w.write_all(header.value.as_bytes())?;
+            w.write_all(b"\r\n")?;
+        }
+        w.write_all(b"\r\n")?;
+        w.write_all(&self.body)
+    }
+
+    // The serialized head, terminating CRLF included, followed by the body.
+    // A request with a body round-trips through from_bytes_with_body; since
+    // from_bytes reads only the head, it gives the request back without one.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_string().into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    pub fn semantically_eq(&self, other: &HttpRequest) -> bool {
+        fn count(headers: &[HttpHeader], header: &HttpHeader) -> usize {
+            headers.iter()
+                   .filter(|h| h.name == header.name && h.value == header.value)
+                   .count()
+        }
+        self.method == other.method &&
+            self.uri == other.uri &&
+            self.version == other.version &&
+            self.body == other.body &&
+            self.headers.len() == other.headers.len() &&
+            self.headers.iter().all(|h| {
+                count(&self.headers, h) == count(&other.headers, h)
+            })
+    }
+
+    pub fn query_params(&self) -> Vec<(String, String)> {
+        let query = match self.query() {
+            Some(query) => query,
+            None        => return Vec::new()
+        };
+        fn decode(component: &str) -> String {
+            let component = component.replace('+', " ");
+            percent_decode(&component).unwrap_or(component)
+        }
+        query.split_trim("&").iter().filter(|p| !p.is_empty()).map(|p| {
+            let pair = p.splitn_trim(2, "=");
+            (decode(pair[0]), decode(pair.get(1).unwrap_or(&"")))
+        }).collect()
+    }
+
+    pub fn decoded_path(&self) -> Result<String, HttpParseError> {
+        percent_decode(self.path())
+    }
+
+    // The path with `.` and `..` segments resolved as in RFC 3986 5.2.4 and
+    // empty segments dropped. `..` at the root stays there, so the result
+    // never climbs above it. Dots written as %2E count too, since the origin
+    // would decode them. Asterisk and authority targets have no path and are
+    // returned as is, as are absolute targets without an authority, like a
+    // urn: whose path isn't hierarchical.
+    pub fn normalized_path(&self) -> String {
+        let path = match (self.target(), self.absolute_uri()) {
+            (RequestTarget::Origin(_), _) => self.path().to_string(),
+            (RequestTarget::Absolute(_), Some((_, path))) => {
+                match path.find('?') {
+                    Some(i) => path[.. i].to_string(),
+                    None    => path
+                }
+            }
+            _ => return self.uri.clone()
+        };
+        let mut segments: Vec<&str> = Vec::new();
+        let mut directory = false;
+        for segment in path.split('/') {
+            let dots = segment.to_ascii_lowercase().replace("%2e", ".");
+            directory = true;
+            match dots.as_str() {
+                "" | "." => {}
+                ".."     => { segments.pop(); }
+                _        => {
+                    segments.push(segment);
+                    directory = false;
+                }
+            }
+        }
+        let mut normalized = format!("/{}", segments.join("/"));
+        if directory && !segments.is_empty() {
+            normalized.push('/');
+        }
+        normalized
+    }
+
+    // The media type and parameter names are lowercased since both are
+    // case-insensitive; parameter values keep their case.
+    pub fn content_type(&self) -> Option<(String, Vec<(String, String)>)> {
+        let value = self.header(&HttpHeaderName::ContentType)?.value();
+        let parts = split_unquoted(value, ';');
+        if parts[0].is_empty() {
+            return None
+        }
+        let mut params = Vec::new();
+        for param in &parts[1 ..] {
+            let pair = param.splitn_trim(2, "=");
+            if pair.len() == 2 && !pair[0].is_empty() {
+                params.push((pair[0].to_ascii_lowercase(), unquote(pair[1])));
+            }
+        }
+        Some((parts[0].to_ascii_lowercase(), params))
+    }
+
+    pub fn multipart_boundary(&self) -> Option<String> {
+        let (media_type, params) = self.content_type()?;
+        if !media_type.starts_with("multipart/") {
+            return None
+        }
+        params.into_iter()
+              .find(|param| param.0 == "boundary")
+              .map(|param| param.1)
+    }
+
+    pub fn accepted_media_types(&self) -> Vec<(String, f32)> {
+        match self.combined_header(&HttpHeaderName::Accept) {
+            Some(accept) => parse_weighted_list(&accept),
+            None         => Vec::new()
+        }
+    }
+
+    // Language ranges by descending q. A `*` range stands for any language
+    // not listed explicitly.
+    pub fn accepted_languages(&self) -> Vec<(String, f32)> {
+        match self.combined_header(&HttpHeaderName::AcceptLanguage) {
+            Some(accept) => parse_weighted_list(&accept),
+            None         => Vec::new()
+        }
+    }
+
+    pub fn accepts_encoding(&self, encoding: &str) -> bool {
+        let name = HttpHeaderName::AcceptEncoding;
+        let accept = match self.combined_header(&name) {
+            Some(accept) => parse_weighted_list(&accept),
+            None         => return true
+        };
+        let weight_of = |coding: &str| {
+            accept.iter()
+                  .find(|pair| pair.0.eq_ignore_ascii_case(coding))
+                  .map(|pair| pair.1)
+        };
+        match weight_of(encoding).or_else(|| weight_of("*")) {
+            Some(weight) => weight > 0.0,
+            None         => encoding.eq_ignore_ascii_case("identity")
+        }
+    }
+
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        let mut cookies = Vec::new();
+        for value in self.header_values(&HttpHeaderName::Cookie) {
+            for cookie in value.split_trim(";") {
+                if cookie.is_empty() {
+                    continue
+                }
+                let pair = cookie.splitn_trim(2, "=");
+                cookies.push((pair[0].to_string(),
+                              pair.get(1).unwrap_or(&"").to_string()));
+            }
+        }
+        cookies
+    }
+
+    pub fn basic_auth(
+        &self
+    ) -> Result<Option<(String, String)>, HttpParseError> {
+        let authorization = match self.header(&HttpHeaderName::Authorization) {
+            Some(header) => header.value(),
+            None         => return Ok(None)
+        };
+        let parts = authorization.splitn_trim(2, " ");
+        if parts.len() != 2 || !parts[0].eq_ignore_ascii_case("basic") {
+            return Ok(None)
+        }
+        let decoded = base64_decode(parts[1])?;
+        let credentials = String::from_utf8(decoded)
+            .map_err(|_| HttpParseError::MalformedCredentials)?;
+        match credentials.find(':') {
+            Some(i) => Ok(Some((credentials[.. i].to_string(),
+                                credentials[i + 1 ..].to_string()))),
+            None    => Err(HttpParseError::MalformedCredentials)
+        }
+    }
+
+    pub fn byte_ranges(
+        &self
+    ) -> Result<Option<Vec<ByteRange>>, HttpParseError> {
+        let range = match self.header(&HttpHeaderName::Range) {
+            Some(header) => header.value(),
+            None         => return Ok(None)
+        };
+        let malformed = || HttpParseError::MalformedRange(range.to_string());
+        let parts = range.splitn_trim(2, "=");
+        if parts.len() != 2 || !parts[0].eq_ignore_ascii_case("bytes") {
+            return Err(malformed())
+        }
+        let mut ranges = Vec::new();
+        let specs = parse_list(parts[1]);
+        if specs.is_empty() {
+            return Err(malformed())
+        }
+        for spec in specs {
+            let bounds = spec.splitn_trim(2, "-");
+            if bounds.len() != 2 {
+                return Err(malformed())
+            }
+            let parse = |bound: &str| -> Result<Option<u64>, HttpParseError> {
+                if bound.is_empty() {
+                    Ok(None)
+                } else if bound.bytes().all(|b| b.is_ascii_digit()) {
+                    bound.parse().map(Some).map_err(|_| malformed())
+                } else {
+                    Err(malformed())
+                }
+            };
+            let (first, last) = (parse(bounds[0])?, parse(bounds[1])?);
+            match (first, last) {
+                (None, None) => return Err(malformed()),
+                (Some(first), Some(last)) if first > last =>
+                    return Err(malformed()),
+                _ => ranges.push((first, last))
+            }
+        }
+        Ok(Some(ranges))
+    }
+
+    // An unparsable Content-Length counts as no body; from_bytes_with_body
+    // reports it as an error instead.
+    pub fn has_body(&self) -> bool {
+        self.is_chunked() ||
+            matches!(self.content_length(), Ok(Some(length)) if length > 0)
+    }
+
+    // Whether chunked framing ends the body. A misplaced chunked coding
+    // doesn't count; from_bytes_with_body rejects it.
+    fn is_chunked(&self) -> bool {
+        self.transfer_codings().is_ok_and(|codings| {
+            codings.last().is_some_and(|coding| coding == "chunked")
+        })
+    }
+
+    // The transfer codings in the order they were applied, lowercased and
+    // without parameters, across all Transfer-Encoding headers. Chunked
+    // frames the message, so RFC 7230 3.3.1 only allows it once and last.
+    pub fn transfer_codings(&self) -> Result<Vec<String>, HttpParseError> {
+        let mut codings = Vec::new();
+        for value in self.header_values(&HttpHeaderName::TransferEncoding) {
+            for coding in parse_list(value) {
+                let name = coding.split(';').next().unwrap_or(coding);
+                codings.push(name.trim().to_ascii_lowercase());
+            }
+        }
+        let chunked = codings.iter().filter(|c| *c == "chunked").count();
+        let last_chunked = codings.last().is_some_and(|c| c == "chunked");
+        if chunked > 1 || (chunked == 1 && !last_chunked) {
+            return Err(HttpParseError::ChunkedNotFinal)
+        }
+        Ok(codings)
+    }
+
+    // Whether a proxy may resend the request after an upstream failure. Only
+    // idempotent methods qualify, and only if there is no body that might
+    // have to be replayed or the client sent an Idempotency-Key for the
+    // server to deduplicate on.
+    pub fn is_safely_retryable(&self) -> bool {
+        self.method.is_idempotent() &&
+            (!self.has_body() || self.header_str("Idempotency-Key").is_some())
+    }
+
+    pub fn is_keep_alive(&self) -> bool {
+        match self.version {
+            HttpVersion::Http11 | HttpVersion::Http20 =>
+                !self.has_connection_option("close"),
+            _ => self.has_connection_option("keep-alive")
+        }
+    }
+
+    pub fn expects_continue(&self) -> bool {
+        self.header(&HttpHeaderName::Expect)
+            .is_some_and(|h| h.value.eq_ignore_ascii_case("100-continue"))
+    }
+
+    pub fn is_conditional(&self) -> bool {
+        self.headers.iter().any(|h| {
+            matches!(h.name, HttpHeaderName::IfMatch |
+                             HttpHeaderName::IfModifiedSince |
+                             HttpHeaderName::IfNoneMatch |
+                             HttpHeaderName::IfUnmodifiedSince)
+        })
+    }
+
+    // The listed entity-tags with their quotes stripped, split only outside
+    // the quotes since a tag may contain a comma. Elements that aren't
+    // entity-tags or the `*` wildcard are skipped, as they can't match.
+    pub fn if_none_match(&self) -> Vec<EntityTag> {
+        let mut etags = Vec::new();
+        for value in self.header_values(&HttpHeaderName::IfNoneMatch) {
+            for etag in split_unquoted(value, ',') {
+                if etag == "*" {
+                    etags.push(EntityTag::Any);
+                    continue
+                }
+                let (weak, opaque) = match etag.strip_prefix("W/") {
+                    Some(opaque) => (true, opaque),
+                    None         => (false, etag)
+                };
+                if opaque.len() >= 2 &&
+                   opaque.starts_with('"') &&
+                   opaque.ends_with('"') {
+                    etags.push(EntityTag::Tag {
+                        weak,
+                        opaque: opaque[1 .. opaque.len() - 1].to_string()
+                    });
+                }
+            }
+        }
+        etags
+    }
+
+    // The first protocol the Upgrade header offers, provided the Connection
+    // header also carries the upgrade option.
+    pub fn upgrade_protocol(&self) -> Option<&str> {
+        if !self.has_connection_option("upgrade") {
+            return None
+        }
+        let upgrade = self.header(&HttpHeaderName::Upgrade)?.value();
+        parse_list(upgrade).first().cloned()
+    }
+
+    fn has_connection_option(&self, option: &str) -> bool {
+        self.header_values(&HttpHeaderName::Connection).iter().any(|value| {
+            parse_list(value).iter().any(|o| o.eq_ignore_ascii_case(option))
+        })
+    }
+
+    // Unlike upgrade_protocol, websocket may be any of the offered
+    // protocols, and the handshake is only complete with a non-empty key.
+    pub fn is_websocket_upgrade(&self) -> bool {
+        self.has_connection_option("upgrade") &&
+            self.header_values(&HttpHeaderName::Upgrade).iter().any(|value| {
+                parse_list(value).iter()
+                    .any(|p| p.eq_ignore_ascii_case("websocket"))
+            }) &&
+            self.websocket_key().is_some()
+    }
+
+    pub fn websocket_key(&self) -> Option<&str> {
+        self.header_str("Sec-WebSocket-Key")
+            .map(|key| key.trim())
+            .filter(|key| !key.is_empty())
+    }
+
+    pub fn host_port(&self) -> Option<(String, Option<u16>)> {
+        let host = self.header(&HttpHeaderName::Host)?.value();
+        // An IPv6 literal is bracketed and contains colons of its own.
+        let port_start = match host.rfind(':') {
+            Some(i) if !host[i ..].contains(']') => i,
+            _ => return Some((host.to_string(), None))
+        };
+        let port = host[port_start + 1 ..].parse::<u16>().ok();
+        Some((host[.. port_start].to_string(), port))
+    }
+
+    // Classified the way check_uri validates targets: authority form is
+    // CONNECT's, and any target starting with a scheme is absolute, whether or
+    // not an authority follows. A schemeless target, which only a builder can
+    // produce, is taken as a path.
+    pub fn target(&self) -> RequestTarget<'_> {
+        if self.method == HttpMethod::Connect {
+            RequestTarget::Authority(&self.uri)
+        } else if self.uri == "*" {
+            RequestTarget::Asterisk
+        } else if !self.uri.starts_with('/') && has_scheme(&self.uri) {
+            RequestTarget::Absolute(&self.uri)
+        } else {
+            RequestTarget::Origin(&self.uri)
+        }
+    }
+
+    // The URI the request is for, per RFC 7230 5.5: the target itself when
+    // it is absolute, else `default_scheme` with the authority and path. The
+    // authority comes from the Host header, or the target in CONNECT's
+    // authority form. An asterisk target contributes no path.
+    pub fn effective_uri(&self, default_scheme: &str) -> Option<String> {
+        let (authority, path) = match self.target() {
+            RequestTarget::Absolute(uri)  => return Some(uri.to_string()),
+            RequestTarget::Authority(uri) => (Some(uri), ""),
+            RequestTarget::Origin(path)   => (None, path),
+            RequestTarget::Asterisk       => (None, "")
+        };
+        let authority = match authority {
+            Some(authority) => authority,
+            None            => {
+                self.header_str("Host").filter(|host| !host.is_empty())?
+            }
+        };
+        Some(format!("{}://{}{}", default_scheme, authority, path))
+    }
+
+    pub fn absolute_uri(&self) -> Option<(String, String)> {
+        split_absolute_uri(&self.uri).map(|(_, authority, path)| {
+            let host = match authority.rfind('@') {
+                Some(i) => &authority[i + 1 ..],
+                None    => authority
+            };
+            let path = if path.starts_with('/') {
+                path.to_string()
+            } else {
+                format!("/{}", path)
+            };
+            (host.to_string(), path)
+        })
+    }
+
+    // A borrowed view of this request for code written against
+    // HttpRequestRef. Headers are read from the owned list, not reparsed.
+    pub fn as_ref_view(&self) -> HttpRequestRef<'_> {
+        HttpRequestRef::from_list(self.method.as_str(),
+                                  &self.uri,
+                                  self.version.as_str(),
+                                  &self.headers)
+    }
+
+    pub fn cache_key(&self, vary: &[HttpHeaderName]) -> String {
+        self.cache_key_with(vary, &CacheKeyOptions::default())
+    }
+
+    // The method and the effective URI with its path normalized, then one
+    // `name: values` line for each header in `vary`. Origin-form targets
+    // take their host from the Host header and the http scheme, so an https
+    // entry is only shared with requests for https. Targets without a path,
+    // like CONNECT's, are used as they are. The vary names are sorted and the
+    // values canonicalized, so neither the order of a Vary header nor stray
+    // whitespace splits entries.
+    pub fn cache_key_with(&self, vary: &[HttpHeaderName],
+                          options: &CacheKeyOptions) -> String {
+        let split = split_absolute_uri(&self.uri);
+        let origin = match (self.target(), split, self.absolute_uri()) {
+            (RequestTarget::Origin(_), _, _) => {
+                let host = self.header_str("Host").unwrap_or("");
+                Some(("http", host.to_string()))
+            }
+            (RequestTarget::Absolute(_), Some((scheme, _, _)),
+             Some((host, _))) => Some((scheme, host)),
+            _ => None
+        };
+        let target = match origin {
+            Some((scheme, host)) => {
+                let query = match self.query() {
+                    Some(query) if options.sort_query => {
+                        let mut params: Vec<&str> = query
+                            .split('&')
+                            .filter(|p| !p.is_empty())
+                            .collect();
+                        params.sort_unstable();
+                        format!("?{}", params.join("&"))
+                    }
+                    Some(query) => format!("?{}", query),
+                    None        => String::new()
+                };
+                format!("{}://{}{}{}", scheme.to_ascii_lowercase(),
+                        host.to_ascii_lowercase(), self.normalized_path(),
+                        query)
+            }
+            None => self.uri.clone()
+        };
+        let mut key = format!("{} {}", self.method, target);
+        let mut names: Vec<&HttpHeaderName> = vary.iter().collect();
+        names.sort_by_key(|name| name.wire_name().to_ascii_lowercase());
+        names.dedup();
+        for name in names {
+            let values: Vec<String> = self.headers
+                .iter()
+                .filter(|h| h.name == *name)
+                .map(|h| h.canonicalized_value())
+                .collect();
+            key.push('\n');
+            key.push_str(&name.wire_name().to_ascii_lowercase());
+            key.push_str(": ");
+            key.push_str(&values.join(", "));
+        }
+        key
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for HttpRequest {
+    type Error = HttpParseError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<HttpRequest, HttpParseError> {
+        HttpRequest::from_bytes(bytes)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for HttpRequest {
+    type Error = HttpParseError;
+
+    fn try_from(header: &'a str) -> Result<HttpRequest, HttpParseError> {
+        HttpRequest::new(header)
+    }
+}
+
+impl From<HttpRequest> for Vec<u8> {
+    fn from(request: HttpRequest) -> Vec<u8> {
+        request.to_bytes()
+    }
+}
+
+impl<'a> IntoIterator for &'a HttpRequest {
+    type Item = &'a HttpHeader;
+    type IntoIter = slice::Iter<'a, HttpHeader>;
+
+    fn into_iter(self) -> slice::Iter<'a, HttpHeader> {
+        self.headers.iter()
+    }
+}
+
+// The head only, since the body need not be text; `to_bytes` and `write_to`
+// append it.
+impl fmt::Display for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}\r\n", self.method, self.uri, self.version)?;
+        for header in &self.headers {
+            write!(f, "{}\r\n", header)?;
+        }
+        f.write_str("\r\n")
+    }
+}
+
+// A decoded chunked body and the trailer headers that followed it.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct ChunkedBody {
+    body:     Vec<u8>,
+    trailers: Vec<HttpHeader>
+}
+
+impl ChunkedBody {
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn trailers(&self) -> &[HttpHeader] {
+        &self.trailers
+    }
+
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+// A (first, last) pair of byte positions. A suffix range like "-500" has no
+// first byte and an open-ended range like "500-" has no last byte.
+pub type ByteRange = (Option<u64>, Option<u64>);
+
+// An entity-tag from a conditional header (RFC 7232 2.3). `Any` is the
+// unquoted `*` wildcard; a quoted "*" is an ordinary tag.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub enum EntityTag {
+    Any,
+    Tag { weak: bool, opaque: String }
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum RequestTarget<'a> {
+    Origin(&'a str),
+    Absolute(&'a str),
+    Authority(&'a str),
+    Asterisk
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct ParseLimits {
+    pub max_header_count:        usize,
+    pub max_header_line_length:  usize,
+    pub max_header_bytes:        usize,
+    pub max_leading_empty_lines: usize
+}
+
+impl Default for ParseLimits {
+    fn default() -> ParseLimits {
+        ParseLimits {
+            max_header_count:        100,
+            max_header_line_length:  8 * 1024,
+            max_header_bytes:        64 * 1024,
+            max_leading_empty_lines: DEFAULT_MAX_LEADING_EMPTY_LINES
+        }
+    }
+}
+
+// Controls how `cache_key_with` canonicalizes a request. Sorting the query
+// lets `?a=1&b=2` and `?b=2&a=1` share an entry, which is only correct when
+// the origin ignores parameter order.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Default)]
+pub struct CacheKeyOptions {
+    pub sort_query: bool
+}
+
+// Controls how a parsed request is re-emitted. By default header names keep
+// the casing they arrived with; lowercasing them matches HTTP/2 conventions.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Default)]
+pub struct SerializeOptions {
+    pub lowercase_header_names: bool
+}
+
+pub struct HttpRequestBuilder {
+    method:  HttpMethod,
+    uri:     String,
+    version: HttpVersion,
+    headers: Vec<HttpHeader>
+}
+
+impl HttpRequestBuilder {
+    pub fn new() -> HttpRequestBuilder {
+        HttpRequestBuilder {
+            method:  HttpMethod::Get,
+            uri:     "/".to_string(),
+            version: HttpVersion::Http11,
+            headers: Vec::new()
+        }
+    }
+
+    pub fn method(mut self, method: HttpMethod) -> HttpRequestBuilder {
+        self.method = method;
+        self
+    }
+
+    pub fn uri(mut self, uri: &str) -> HttpRequestBuilder {
+        self.uri = uri.to_string();
+        self
+    }
+
+    pub fn version(mut self, version: HttpVersion) -> HttpRequestBuilder {
+        self.version = version;
+        self
+    }
+
+    pub fn header(
+        mut self,
+        name:  HttpHeaderName,
+        value: &str
+    ) -> HttpRequestBuilder {
+        let original_name = name.to_string();
+        self.headers.push(HttpHeader {
+            name,
+            original_name,
+            value: value.to_string()
+        });
+        self
+    }
+
+    pub fn build(self) -> HttpRequest {
+        HttpRequest {
+            method:  self.method,
+            uri:     self.uri,
+            version: self.version,
+            headers: self.headers,
+            body:    Vec::new()
+        }
+    }
+}
+
+impl Default for HttpRequestBuilder {
+    fn default() -> HttpRequestBuilder {
+        HttpRequestBuilder::new()
+    }
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum ParseProgress {
+    NeedMore,
+    Complete(HttpRequest),
+    Error(HttpParseError)
+}
+
+// Parses a single request head. Once `feed` has returned `Complete` or
+// `Error` the parser is finished and further calls report ParserFinished; use
+// a new parser for the next request on the connection.
+pub struct RequestParser {
+    buffer:     Vec<u8>,
+    header_len: usize,
+    limits:     ParseLimits,
+    finished:   bool
+}
+
+impl RequestParser {
+    pub fn new() -> RequestParser {
+        RequestParser::with_limits(ParseLimits::default())
+    }
+
+    // The limits are checked as bytes arrive, so a head that never ends
+    // can't grow the buffer past `max_header_bytes`.
+    pub fn with_limits(limits: ParseLimits) -> RequestParser {
+        RequestParser {
+            buffer:     Vec::new(),
+            header_len: 0,
+            limits,
+            finished:   false
+        }
+    }
+
+    // Bytes fed up to and including the CRLFCRLF ending the header block,
+    // 0 until the block is complete. Anything fed past it is the body.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> ParseProgress {
+        if self.finished {
+            return ParseProgress::Error(HttpParseError::ParserFinished)
+        }
+        // The terminator may straddle the previous and the new bytes, and
+        // the empty lines allowed before the request line don't end a head.
+        let search_start = self.buffer.len().saturating_sub(3);
+        self.buffer.extend_from_slice(bytes);
+        let skip = skip_empty_lines(&self.buffer,
+                                    self.limits.max_leading_empty_lines,
+                                    false);
+        let search_start = search_start.max(skip);
+        let progress = match find(&self.buffer[search_start ..], b"\r\n\r\n") {
+            Some(i) => {
+                let head_len = search_start + i + 4;
+                self.header_len = head_len;
+                let head = head_str(&self.buffer[.. head_len]);
+                match head.and_then(|head| {
+                    HttpRequest::new_with_limits(head, &self.limits)
+                }) {
+                    Ok(request) => ParseProgress::Complete(request),
+                    Err(e)      => ParseProgress::Error(e)
+                }
+            },
+            None if self.buffer.len() - skip > self.limits.max_header_bytes => {
+                ParseProgress::Error(HttpParseError::HeaderBlockTooLarge)
+            }
+            None => return ParseProgress::NeedMore
+        };
+        self.finished = true;
+        progress
+    }
+}
+
+impl Default for RequestParser {
+    fn default() -> RequestParser {
+        RequestParser::new()
+    }
+}
+
+// Parses a complete request, head and body, from untrusted bytes. As with
+// `HttpRequest::from_bytes_with_body`, a chunked body is kept encoded. It must
+// return an error rather than panic on any input; the fuzz/ directory holds
+// a cargo-fuzz target that checks this.
+pub fn parse_request(bytes: &[u8]) -> Result<HttpRequest, HttpParseError> {
+    HttpRequest::from_bytes_with_body(bytes)
+}
+
+// Parses the complete requests at the start of a pipelined buffer, returning
+// them with the number of bytes they took up. A trailing partial request is
+// left for the caller to complete with more input. Parsing stops before a
+// malformed request, so the ones ahead of it are still returned; the error is
+// only returned when the buffer starts with it, as it does on the caller's
+// next call. Chunked bodies are kept encoded, as they arrived.
+pub fn parse_requests(
+    bytes: &[u8]
+) -> Result<(Vec<HttpRequest>, usize), HttpParseError> {
+    let mut requests = Vec::new();
+    let mut consumed = 0;
+    loop {
+        let rest = &bytes[consumed ..];
+        let skip = leading_empty_lines_len(rest, false);
+        if find(&rest[skip ..], b"\r\n\r\n").is_none() {
+            break
+        }
+        let request = match HttpRequest::from_bytes_with_body(rest) {
+            Ok(request)                                => request,
+            Err(HttpParseError::IncompleteBody { .. }) |
+            Err(HttpParseError::IncompleteChunk)       => break,
+            Err(_) if !requests.is_empty()             => break,
+            Err(e)                                     => return Err(e)
+        };
+        consumed += skip + head_len(&rest[skip ..]) + request.body.len();
+        requests.push(request);
+    }
+    Ok((requests, consumed))
+}
+
+// Parses just the request line, for routing on the method and target without
+// the cost of parsing headers. Parsing is as strict as `HttpRequest::new`; a
+// trailing CRLF is ignored.
+pub fn parse_request_line(
+    line: &str
+) -> Result<(HttpMethod, String, HttpVersion), HttpParseError> {
+    request_line(line.strip_suffix("\r\n").unwrap_or(line), false)
+}
+
+fn request_line(
+    line:    &str,
+    lenient: bool
+) -> Result<(HttpMethod, String, HttpVersion), HttpParseError> {
+    let (method, uri, version) = split_request_line(line, lenient)
+        .map_err(|e| e.into_parse_error(line))?;
+    Ok((method.parse::<HttpMethod>()?,
+        uri.to_string(),
+        HttpVersion::new(version)))
+}
+
+pub fn decode_chunked(bytes: &[u8]) -> Result<Vec<u8>, HttpParseError> {
+    decode_chunked_with_trailers(bytes).map(|chunked| chunked.body)
+}
+
+// Like `decode_chunked`, but also returns the trailer headers sent after the
+// last chunk.
+pub fn decode_chunked_with_trailers(
+    bytes: &[u8]
+) -> Result<ChunkedBody, HttpParseError> {
+    parse_chunked(bytes).map(|(chunked, _)| chunked)
+}
+
+// The decoded body and the number of bytes its encoding took up, trailers
+// included, so a pipelined request after it can be found.
+fn parse_chunked(
+    bytes: &[u8]
+) -> Result<(ChunkedBody, usize), HttpParseError> {
+    let mut decoded = Vec::new();
+    let mut pos = 0;
+    loop {
+        let line_end = match find(&bytes[pos ..], b"\r\n") {
+            Some(i) => pos + i,
+            None    => return Err(HttpParseError::IncompleteChunk)
+        };
+        let size = parse_chunk_size(&bytes[pos .. line_end])?;
+        pos = line_end + 2;
+        if size == 0 {
+            let (trailers, len) = parse_trailers(&bytes[pos ..])?;
+            let chunked = ChunkedBody { body: decoded, trailers };
+            return Ok((chunked, pos + len))
+        }
+        let data_end = match pos.checked_add(size) {
+            Some(end) if end < bytes.len().saturating_sub(1) => end,
+            _ => return Err(HttpParseError::IncompleteChunk)
+        };
+        if &bytes[data_end .. data_end + 2] != b"\r\n" {
+            return Err(HttpParseError::MalformedChunk)
+        }
+        decoded.extend_from_slice(&bytes[pos .. data_end]);
+        pos = data_end + 2;
+    }
+}
+
+// The trailer section: header lines up to an empty line. Also returns its
+// length including that line.
+fn parse_trailers(
+    bytes: &[u8]
+) -> Result<(Vec<HttpHeader>, usize), HttpParseError> {
+    let mut trailers = Vec::new();
+    let mut pos = 0;
+    loop {
+        let line_end = match find(&bytes[pos ..], b"\r\n") {
+            Some(i) => pos + i,
+            None    => return Err(HttpParseError::IncompleteChunk)
+        };
+        let line = &bytes[pos .. line_end];
+        if line.is_empty() {
+            return Ok((trailers, line_end + 2))
+        }
+        let malformed = || {
+            HttpParseError::MalformedHeader(
+                String::from_utf8_lossy(line).into_owned())
+        };
+        if line[0] == b' ' || line[0] == b'\t' {
+            return Err(malformed())
+        }
+        let line = str::from_utf8(line).map_err(|_| malformed())?;
+        let trailer = HttpHeader::new(line)?;
+        let name = &trailer.original_name;
+        if FORBIDDEN_TRAILERS.iter().any(|f| f.eq_ignore_ascii_case(name)) {
+            return Err(HttpParseError::ForbiddenTrailer(trailer.original_name))
+        }
+        trailers.push(trailer);
+        pos = line_end + 2;
+    }
+}
+
+fn parse_chunk_size(line: &[u8]) -> Result<usize, HttpParseError> {
+    let malformed = || {
+        HttpParseError::MalformedChunkSize(
+            String::from_utf8_lossy(line).into_owned())
+    };
+    // Chunk extensions after ';' are ignored.
+    let size = line.split(|&b| b == b';').next().unwrap_or(line);
+    let size = str::from_utf8(size).map_err(|_| malformed())?.trim();
+    if size.is_empty() || !size.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(malformed())
+    }
+    usize::from_str_radix(size, 16).map_err(|_| malformed())
+}
+
+// The head as text, once checked to be ASCII where the grammar requires it.
+fn head_str(head: &[u8]) -> Result<&str, HttpParseError> {
+    check_ascii(head)?;
+    str::from_utf8(head)
+        .map_err(|e| HttpParseError::InvalidUtf8(e.valid_up_to()))
+}
+
+// The request line counts towards the line length and total size limits but
+// not the header count.
+fn check_limits(
+    lines:  &[&str],
+    limits: &ParseLimits
+) -> Result<(), HttpParseError> {
+    let mut count = 0;
+    let mut bytes = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 && line.is_empty() {
+            break
+        }
+        if line.len() > limits.max_header_line_length {
+            return Err(HttpParseError::HeaderLineTooLong)
+        }
+        bytes += line.len() + 2;
+        if bytes > limits.max_header_bytes {
+            return Err(HttpParseError::HeaderBlockTooLarge)
+        }
+        if i > 0 && !line.starts_with(' ') && !line.starts_with('\t') {
+            count += 1;
+            if count > limits.max_header_count {
+                return Err(HttpParseError::TooManyHeaders)
+            }
+        }
+    }
+    Ok(())
+}
+
+fn base64_decode(input: &str) -> Result<Vec<u8>, HttpParseError> {
+    fn sextet(b: u8) -> Option<u32> {
+        match b {
+            b'A' ..= b'Z' => Some((b - b'A') as u32),
+            b'a' ..= b'z' => Some((b - b'a') as u32 + 26),
+            b'0' ..= b'9' => Some((b - b'0') as u32 + 52),
+            b'+'          => Some(62),
+            b'/'          => Some(63),
+            _             => None
+        }
+    }
+    let invalid = || HttpParseError::InvalidBase64(input.to_string());
+    let bytes = input.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(invalid())
+    }
+    let mut decoded = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (i, quad) in bytes.chunks(4).enumerate() {
+        let last = i == bytes.len() / 4 - 1;
+        let padding = quad.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return Err(invalid())
+        }
+        let mut bits: u32 = 0;
+        for &b in &quad[.. 4 - padding] {
+            bits = bits << 6 | sextet(b).ok_or_else(invalid)?;
+        }
+        bits <<= 6 * padding as u32;
+        decoded.push((bits >> 16) as u8);
+        if padding < 2 {
+            decoded.push((bits >> 8) as u8);
+        }
+        if padding < 1 {
+            decoded.push(bits as u8);
+        }
+    }
+    Ok(decoded)
+}
+
+// Splits an absolute-form uri into its scheme, authority and the remaining
+// path and query.
+fn split_absolute_uri(uri: &str) -> Option<(&str, &str, &str)> {
+    let scheme_end = uri.find("://")?;
+    let scheme = &uri[.. scheme_end];
+    if !is_scheme(scheme) {
+        return None
+    }
+    let rest = &uri[scheme_end + 3 ..];
+    let authority_end = rest.find(&['/', '?'][..]).unwrap_or(rest.len());
+    if authority_end == 0 {
+        return None
+    }
+    Some((scheme, &rest[.. authority_end], &rest[authority_end ..]))
+}
+
+fn percent_decode(input: &str) -> Result<String, HttpParseError> {
+    fn hex_value(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let high = bytes.get(i + 1).and_then(|&b| hex_value(b));
+            let low  = bytes.get(i + 2).and_then(|&b| hex_value(b));
+            match (high, low) {
+                (Some(high), Some(low)) => decoded.push(high << 4 | low),
+                _                       => return Err(
+                    HttpParseError::InvalidPercentEncoding(input.to_string()))
+            }
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|e| {
+        HttpParseError::InvalidUtf8(e.utf8_error().valid_up_to())
+    })
+}
+
+// The request line and header names must be ASCII, header values may carry
+// other (UTF-8) bytes.
+fn check_ascii(bytes: &[u8]) -> Result<(), HttpParseError> {
+    let mut request_line = true;
+    let mut line_start   = true;
+    let mut in_value     = false;
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b'\n' {
+            request_line = false;
+            line_start   = true;
+            in_value     = false;
+            continue
+        }
+        let folded = line_start && (b == b' ' || b == b'\t');
+        if !request_line && (b == b':' || folded) {
+            in_value = true;
+        }
+        line_start = false;
+        if !in_value && !b.is_ascii() {
+            return Err(HttpParseError::NonAsciiByte(i))
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use error::HttpParseError;
+    use error::HttpSyntaxError;
+    use method::HttpMethod;
+    use method::HttpVersion;
+    use header::HttpHeader;
+    use header::HttpHeaderName;
+    use super::HttpRequest;
+    use super::ByteRange;
+    use super::CacheKeyOptions;
+    use super::EntityTag;
+    use super::RequestTarget;
+    use super::ParseLimits;
+    use super::HttpRequestRef;
+    use super::HttpRequestBuilder;
+    use super::ParseProgress;
+    use super::RequestParser;
+    use super::decode_chunked;
+    use super::decode_chunked_with_trailers;
+    use super::parse_request;
+    use super::parse_request_line;
+    use super::parse_requests;
+
+            #[test]
+    fn http_get_request() {
+        let get_request_str = "GET /some/path HTTP/1.1\r\n\
+                               Host: http://rsproxy.com\r\n\
+                               Accept: text/html\r\n";
+        let get_request = HttpRequest::new(get_request_str).unwrap();
+        assert_eq!(HttpMethod::Get, get_request.method);
+        assert_eq!("/some/path", get_request.uri);
+        assert_eq!(HttpVersion::Http11, get_request.version);
+        let host_header: &HttpHeader = &(get_request.headers)[0];
+        assert_eq!(HttpHeaderName::Host, host_header.name);
+        assert_eq!("http://rsproxy.com", host_header.value);
+    }
+
+    #[test]
+    fn http_request_accessors() {
+        let request = HttpRequest::new("POST /submit HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n").unwrap();
+        assert_eq!(&HttpMethod::Post, request.method());
+        assert_eq!("/submit", request.uri());
+        assert_eq!(1, request.headers().len());
+        let host_header = &request.headers()[0];
+        assert_eq!(&HttpHeaderName::Host, host_header.name());
+        assert_eq!("rsproxy.com", host_header.value());
+    }
+
+    #[test]
+    fn http_connect_request() {
+        let connect_request = HttpRequest::new(
+            "CONNECT rsproxy.com:443 HTTP/1.1\r\n\
+             Host: rsproxy.com:443\r\n").unwrap();
+        assert_eq!(HttpMethod::Connect, connect_request.method);
+        assert_eq!("rsproxy.com:443", connect_request.uri);
+    }
+
+    #[test]
+    fn http_patch_request() {
+        let patch_request = HttpRequest::new(
+            "PATCH /resource/1 HTTP/1.1\r\nHost: rsproxy.com\r\n").unwrap();
+        assert_eq!(HttpMethod::Patch, patch_request.method);
+    }
+
+    #[test]
+    fn http_head_request() {
+        let head_request =
+            HttpRequest::new("HEAD /path HTTP/1.0\r\n").unwrap();
+        assert_eq!(HttpMethod::Head, head_request.method);
+        let header_request =
+            HttpRequest::new("HEADER /path HTTP/1.0\r\n").unwrap();
+        assert_eq!(HttpMethod::Extension("HEADER".to_string()),
+                   header_request.method);
+    }
+
+    #[test]
+    fn http_request_header_lookup() {
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        X-Request-Id: 42\r\n").unwrap();
+        assert_eq!("rsproxy.com",
+                   request.header(&HttpHeaderName::Host).unwrap().value());
+        assert_eq!(None, request.header(&HttpHeaderName::Accept));
+        assert_eq!(Some("rsproxy.com"), request.header_str("host"));
+        assert_eq!(Some("rsproxy.com"), request.header_str("Host"));
+        assert_eq!(Some("rsproxy.com"), request.header_str("HOST"));
+        assert_eq!(Some("42"), request.header_str("X-Request-Id"));
+        assert_eq!(Some("42"), request.header_str("x-request-id"));
+        assert_eq!(None, request.header_str("Referer"));
+    }
+
+    #[test]
+    fn http_request_display_round_trip() {
+        let request_str = "POST /submit?id=1 HTTP/1.0\r\n\
+                           Host: rsproxy.com\r\n\
+                           user-agent: curl/7.47.0\r\n\
+                           X-Custom: some value\r\n\
+                           \r\n";
+        let request = HttpRequest::new(request_str).unwrap();
+        assert_eq!(request_str, request.to_string());
+        assert_eq!(request, HttpRequest::new(&request.to_string()).unwrap());
+    }
+
+    #[test]
+    fn http_request_malformed_header() {
+        let request_str = "GET / HTTP/1.1\r\n\
+                           Host: rsproxy.com\r\n\
+                           Authorization Basic Zm9vOmJhcg==\r\n";
+        assert_eq!(Err(HttpParseError::MissingHeaderColon(
+                       "Authorization Basic Zm9vOmJhcg==".to_string())),
+                   HttpRequest::new(request_str));
+        let request = HttpRequest::new_lenient(request_str).unwrap();
+        assert_eq!(1, request.headers().len());
+        assert_eq!(Some("rsproxy.com"), request.header_str("Host"));
+    }
+
+    #[test]
+    fn http_request_folded_header() {
+        let request = HttpRequest::new(
+            "GET / HTTP/1.1\r\n\
+             User-Agent: Mozilla/5.0\r\n \
+             (X11; Linux x86_64)\r\n\t\
+             Gecko/20100101\r\n\
+             Host: rsproxy.com\r\n").unwrap();
+        assert_eq!(2, request.headers().len());
+        assert_eq!(Some("Mozilla/5.0 (X11; Linux x86_64) Gecko/20100101"),
+                   request.header_str("User-Agent"));
+        assert_eq!(Some("rsproxy.com"), request.header_str("Host"));
+    }
+
+    #[test]
+    fn http_request_folded_first_header() {
+        let request_str = "GET / HTTP/1.1\r\n \
+                           folded\r\n\
+                           Host: rsproxy.com\r\n";
+        assert_eq!(Err(HttpParseError::MalformedHeader(" folded".to_string())),
+                   HttpRequest::new(request_str));
+        let request = HttpRequest::new_lenient(request_str).unwrap();
+        assert_eq!(1, request.headers().len());
+    }
+
+    #[test]
+    fn http_request_line_token_count() {
+        fn assert_malformed(request_line: &str) {
+            let request_str = format!("{}\r\nHost: rsproxy.com\r\n",
+                                      request_line);
+            assert_eq!(Err(HttpParseError::MalformedRequestLine(
+                           request_line.to_string())),
+                       HttpRequest::new(&request_str));
+        }
+        assert_malformed("GET");
+        assert_malformed("GET /path HTTP/1.1 extra");
+        assert_malformed("GET ");
+    }
+
+    #[test]
+    fn http_request_http09() {
+        let request = HttpRequest::new("GET /path\r\n").unwrap();
+        assert_eq!(&HttpMethod::Get, request.method());
+        assert_eq!("/path", request.uri());
+        assert_eq!(&HttpVersion::Http09, request.version());
+        assert!(request.headers().is_empty());
+        assert_eq!(&HttpVersion::Http09,
+                   HttpRequest::new_lenient("GET  /path\r\n")
+                       .unwrap()
+                       .version());
+        let request = HttpRequestRef::new("GET /path\r\n").unwrap();
+        assert_eq!("HTTP/0.9", request.version());
+        assert_eq!(&HttpVersion::Http09, request.to_owned().version());
+        assert_eq!(Err(HttpParseError::MalformedRequestLine(
+                       "GET /path ".to_string())),
+                   HttpRequest::new("GET /path \r\n"));
+    }
+
+    #[test]
+    fn http_request_line_whitespace() {
+        for request_line in &["GET /path  HTTP/1.1", "GET\t/path HTTP/1.1",
+                              " GET /path HTTP/1.1", "GET /path HTTP/1.1 ",
+                              "GET /path\x0bHTTP/1.1"] {
+            let request_str = format!("{}\r\n", request_line);
+            assert_eq!(Err(HttpParseError::MalformedRequestLine(
+                           request_line.to_string())),
+                       HttpRequest::new(&request_str));
+            assert_eq!(Err(HttpSyntaxError::MalformedRequestLine(0)),
+                       HttpRequestRef::new(&request_str));
+            let request = HttpRequest::new_lenient(&request_str).unwrap();
+            assert_eq!("/path", request.uri());
+            assert_eq!(&HttpVersion::Http11, request.version());
+        }
+    }
+
+    #[test]
+    fn http_request_empty_input() {
+        assert_eq!(Err(HttpParseError::MissingRequestLine),
+                   HttpRequest::new(""));
+        assert_eq!(Err(HttpParseError::MissingRequestLine),
+                   HttpRequest::new("\r\n"));
+        assert_eq!(Err(HttpParseError::MissingRequestLine),
+                   HttpRequest::new(" \t\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::MissingRequestLine),
+                   HttpRequest::new_lenient(""));
+    }
+
+    #[test]
+    fn http_request_from_bytes() {
+        let request = HttpRequest::from_bytes(
+            b"GET /caf\xc3\xa9 HTTP/1.1\r\nHost: rsproxy.com\r\n");
+        assert_eq!(Err(HttpParseError::NonAsciiByte(8)), request);
+        let request = HttpRequest::from_bytes(
+            b"GET / HTTP/1.1\r\nH\xc3\xb6st: rsproxy.com\r\n");
+        assert_eq!(Err(HttpParseError::NonAsciiByte(17)), request);
+        let request = HttpRequest::from_bytes(
+            b"GET / HTTP/1.1\r\nX-Name: \xff\r\n");
+        assert_eq!(Err(HttpParseError::InvalidUtf8(24)), request);
+        let request = HttpRequest::from_bytes(
+            b"GET / HTTP/1.0\r\nX-Name: Andr\xc3\xa9\r\n").unwrap();
+        assert_eq!(Some("Andr\u{e9}"), request.header_str("X-Name"));
+    }
+
+    #[test]
+    fn http_request_try_from_bytes() {
+        use core::convert::TryFrom;
+        use core::convert::TryInto;
+
+        fn parse(bytes: &[u8]) -> Result<HttpRequest, HttpParseError> {
+            let request: HttpRequest = bytes.try_into()?;
+            Ok(request)
+        }
+        let bytes = b"GET / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n";
+        assert_eq!(HttpRequest::from_bytes(bytes), parse(bytes));
+        assert_eq!(Err(HttpParseError::NonAsciiByte(4)),
+                   HttpRequest::try_from(&b"GET \xff HTTP/1.1\r\n"[..]));
+    }
+
+    #[test]
+    fn http_request_try_from_str() {
+        use core::convert::TryFrom;
+        use core::convert::TryInto;
+
+        let header = "GET /index.html HTTP/1.1\r\nHost: rsproxy.com\r\n";
+        let request: HttpRequest = header.try_into().unwrap();
+        assert_eq!(HttpRequest::new(header).unwrap(), request);
+        assert_eq!(Err(HttpParseError::MissingRequestLine),
+                   HttpRequest::try_from(""));
+    }
+
+    #[test]
+    fn http_request_with_uri() {
+        let request = HttpRequest::new("POST /api/v1/users?id=7 HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Accept: */*\r\n").unwrap();
+        let rewritten = request.with_uri(&request.uri()["/api".len() ..]);
+        assert_eq!("/v1/users?id=7", rewritten.uri());
+        assert_eq!(request.method(), rewritten.method());
+        assert_eq!(request.version(), rewritten.version());
+        assert_eq!(request.headers(), rewritten.headers());
+        assert_eq!("/api/v1/users?id=7", request.uri());
+        assert_eq!("*", request.with_uri(String::from("*")).uri());
+    }
+
+    #[test]
+    fn http_request_header_mutation() {
+        let mut request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                            host: rsproxy.com\r\n\
+                                            Cookie: a=1\r\n\
+                                            Accept: */*\r\n\
+                                            Cookie: b=2\r\n").unwrap();
+        request.add_header(HttpHeaderName::Accept, "text/html");
+        assert_eq!(vec!["*/*", "text/html"],
+                   request.header_values(&HttpHeaderName::Accept));
+        assert_eq!("Accept: text/html", request.headers()[4].to_string());
+
+        request.remove_header(&HttpHeaderName::Cookie);
+        assert!(request.header_values(&HttpHeaderName::Cookie).is_empty());
+        assert_eq!(3, request.headers().len());
+        request.remove_header(&HttpHeaderName::Referer);
+        assert_eq!(3, request.headers().len());
+
+        request.set_header(HttpHeaderName::Host, "example.com");
+        request.set_header(HttpHeaderName::Accept, "*/*");
+        request.set_header(HttpHeaderName::UserAgent, "rsproxy");
+        assert_eq!(vec!["host: example.com", "Accept: */*",
+                        "User-Agent: rsproxy"],
+                   request.headers_iter()
+                          .map(|h| h.to_string())
+                          .collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn http_request_strip_hop_by_hop() {
+        let mut request = HttpRequest::new(
+            "GET / HTTP/1.1\r\n\
+             Host: rsproxy.com\r\n\
+             Connection: keep-alive, X-Trace\r\n\
+             connection: Cookie\r\n\
+             Keep-Alive: timeout=5\r\n\
+             Upgrade: websocket\r\n\
+             Proxy-Authorization: Basic Zm9vOmJhcg==\r\n\
+             x-trace: 1\r\n\
+             Cookie: a=1\r\n\
+             Accept: */*\r\n").unwrap();
+        request.strip_hop_by_hop();
+        assert_eq!(vec!["Host: rsproxy.com", "Accept: */*"],
+                   request.headers_iter()
+                          .map(|h| h.to_string())
+                          .collect::<Vec<_>>());
+
+        let mut request = HttpRequest::new(
+            "POST / HTTP/1.1\r\n\
+             Host: rsproxy.com\r\n\
+             Connection: Content-Length, Host\r\n\
+             Content-Length: 5\r\n").unwrap();
+        request.strip_hop_by_hop();
+        assert_eq!(vec!["Host: rsproxy.com", "Content-Length: 5"],
+                   request.headers_iter()
+                          .map(|h| h.to_string())
+                          .collect::<Vec<_>>());
+
+        // A chunked body is reframed, so the next hop still reads one request.
+        let mut request = parse_request(b"POST /a HTTP/1.1\r\nHost: a\r\n\
+                                          Transfer-Encoding: chunked\r\n\r\n\
+                                          5\r\nhello\r\n0\r\n\r\n").unwrap();
+        request.strip_hop_by_hop();
+        let bytes = request.to_bytes();
+        assert_eq!(b"POST /a HTTP/1.1\r\nHost: a\r\nContent-Length: 5\r\n\
+                     \r\nhello".to_vec(),
+                   bytes);
+        let (requests, consumed) = parse_requests(&bytes).unwrap();
+        assert_eq!(vec![request], requests);
+        assert_eq!(bytes.len(), consumed);
+
+        // Without the body the framing has to stay as it arrived.
+        let mut request = HttpRequest::new("POST /a HTTP/1.1\r\nHost: a\r\n\
+                                            Transfer-Encoding: chunked\r\n\
+                                            Keep-Alive: timeout=5\r\n")
+            .unwrap();
+        request.strip_hop_by_hop();
+        assert_eq!(vec!["Host: a", "Transfer-Encoding: chunked"],
+                   request.headers_iter()
+                          .map(|h| h.to_string())
+                          .collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn http_request_append_forwarded_for() {
+        use std::net::IpAddr;
+        use std::net::Ipv4Addr;
+        use std::net::Ipv6Addr;
+
+        let mut request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                            Host: rsproxy.com\r\n\
+                                            x-forwarded-for: 203.0.113.7\r\n\
+                                            Accept: */*\r\n").unwrap();
+        request.append_forwarded_for(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(3, request.headers().len());
+        assert_eq!(Some("203.0.113.7, 10.0.0.1"),
+                   request.header_str("X-Forwarded-For"));
+        assert_eq!("x-forwarded-for", request.headers()[1].original_name());
+
+        let mut request = HttpRequest::new("GET / HTTP/1.0\r\n").unwrap();
+        request.append_forwarded_for(IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!("X-Forwarded-For: ::1", request.headers()[0].to_string());
+    }
+
+    #[test]
+    fn http_request_from_pseudo_headers() {
+        fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+            pairs.iter()
+                 .map(|pair| (pair.0.to_string(), pair.1.to_string()))
+                 .collect()
+        }
+        let request = HttpRequest::from_pseudo_headers(&pairs(&[
+            (":method", "GET"),
+            (":scheme", "https"),
+            (":authority", "rsproxy.com"),
+            (":path", "/index.html?q=1"),
+            ("accept", "*/*"),
+            ("cookie", "a=1")
+        ])).unwrap();
+        assert_eq!("GET /index.html?q=1 HTTP/1.1\r\n\
+                    Host: rsproxy.com\r\n\
+                    accept: */*\r\n\
+                    cookie: a=1\r\n\
+                    \r\n",
+                   request.to_string());
+
+        let request = HttpRequest::from_pseudo_headers(&pairs(&[
+            (":method", "CONNECT"),
+            (":authority", "rsproxy.com:443"),
+            ("host", "example.com")
+        ])).unwrap();
+        assert_eq!("rsproxy.com:443", request.uri());
+        assert_eq!(Some("example.com"), request.header_str("Host"));
+        assert_eq!(1, request.headers().len());
+
+        fn error(pairs: Vec<(String, String)>) -> HttpParseError {
+            HttpRequest::from_pseudo_headers(&pairs).unwrap_err()
+        }
+        assert_eq!(HttpParseError::MissingPseudoHeader(":path".to_string()),
+                   error(pairs(&[(":method", "GET"), (":scheme", "http")])));
+        assert_eq!(HttpParseError::MissingPseudoHeader(":method".to_string()),
+                   error(pairs(&[(":scheme", "http"), (":path", "/")])));
+        assert_eq!(HttpParseError::InvalidPseudoHeader(":path".to_string()),
+                   error(pairs(&[(":method", "GET"), (":path", "/"),
+                                 (":path", "/")])));
+        assert_eq!(HttpParseError::InvalidPseudoHeader(":path".to_string()),
+                   error(pairs(&[(":method", "GET"), ("accept", "*/*"),
+                                 (":path", "/")])));
+        assert_eq!(HttpParseError::InvalidPseudoHeader(":status".to_string()),
+                   error(pairs(&[(":status", "200")])));
+
+        let invalid = |name: &str| {
+            HttpParseError::InvalidFieldValue(name.to_string())
+        };
+        assert_eq!(invalid(":path"),
+                   error(pairs(&[(":method", "GET"), (":scheme", "http"),
+                                 (":path", "/ HTTP/1.1\r\nHost: a\r\n\r\n\
+                                            GET /admin")])));
+        assert_eq!(invalid(":path"),
+                   error(pairs(&[(":method", "GET"), (":scheme", "http"),
+                                 (":path", "/a b")])));
+        assert_eq!(invalid(":path"),
+                   error(pairs(&[(":method", "GET"), (":scheme", "http"),
+                                 (":path", "/a\tb")])));
+        assert_eq!(invalid(":method"),
+                   error(pairs(&[(":method", "GET /x"), (":scheme", "http"),
+                                 (":path", "/")])));
+        assert_eq!(invalid(":method"),
+                   error(pairs(&[(":method", "GET\0"), (":scheme", "http"),
+                                 (":path", "/")])));
+        assert_eq!(invalid(":authority"),
+                   error(pairs(&[(":method", "CONNECT"),
+                                 (":authority", "a:443\r\nX: y")])));
+        assert_eq!(invalid("content-length"),
+                   error(pairs(&[(":method", "POST"), (":scheme", "http"),
+                                 (":path", "/"),
+                                 ("content-length",
+                                  "1\r\nTransfer-Encoding: chunked")])));
+        assert_eq!(invalid("x-trace"),
+                   error(pairs(&[(":method", "GET"), (":scheme", "http"),
+                                 (":path", "/"), ("x-trace", "a\nb")])));
+        assert_eq!(invalid("x-trace"),
+                   error(pairs(&[(":method", "GET"), (":scheme", "http"),
+                                 (":path", "/"), ("x-trace", "a\0b")])));
+    }
+
+    #[test]
+    fn http_request_to_pseudo_headers() {
+        fn pseudo_headers(header: &str) -> Vec<(String, String)> {
+            HttpRequest::new(header).unwrap().to_pseudo_headers()
+        }
+        fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+            pairs.iter()
+                 .map(|pair| (pair.0.to_string(), pair.1.to_string()))
+                 .collect()
+        }
+        assert_eq!(pairs(&[(":method", "POST"),
+                           (":path", "/submit?id=1"),
+                           (":authority", "rsproxy.com"),
+                           (":scheme", "http"),
+                           ("content-type", "text/plain"),
+                           ("x-custom", "1")]),
+                   pseudo_headers("POST /submit?id=1 HTTP/1.1\r\n\
+                                   Host: rsproxy.com\r\n\
+                                   Connection: keep-alive, X-Trace\r\n\
+                                   Content-Type: text/plain\r\n\
+                                   X-Trace: abc\r\n\
+                                   Transfer-Encoding: chunked\r\n\
+                                   X-Custom: 1\r\n"));
+        assert_eq!(pairs(&[(":method", "POST"),
+                           (":path", "/rpc"),
+                           (":authority", "rsproxy.com"),
+                           (":scheme", "http"),
+                           ("te", "trailers"),
+                           ("trailer", "grpc-status")]),
+                   pseudo_headers("POST /rpc HTTP/1.1\r\n\
+                                   Host: rsproxy.com\r\n\
+                                   Proxy-Connection: keep-alive\r\n\
+                                   TE: Trailers\r\n\
+                                   Trailer: grpc-status\r\n\
+                                   Keep-Alive: timeout=5\r\n"));
+        assert_eq!(pairs(&[(":method", "GET"),
+                           (":path", "/"),
+                           (":authority", "rsproxy.com"),
+                           (":scheme", "http")]),
+                   pseudo_headers("GET / HTTP/1.1\r\n\
+                                   Host: rsproxy.com\r\n\
+                                   TE: trailers, deflate\r\n\
+                                   TE: gzip\r\n"));
+        assert_eq!(pairs(&[(":method", "GET"),
+                           (":path", "/"),
+                           (":authority", "example.com:8080"),
+                           (":scheme", "https")]),
+                   pseudo_headers("GET HTTPS://u:p@example.com:8080 \
+                                   HTTP/1.1\r\n\
+                                   Host: ignored.com\r\n"));
+        assert_eq!(pairs(&[(":method", "CONNECT"),
+                           (":authority", "rsproxy.com:443")]),
+                   pseudo_headers("CONNECT rsproxy.com:443 HTTP/1.1\r\n\
+                                   Host: rsproxy.com:443\r\n"));
+
+        let request = HttpRequest::new("GET /a HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Accept: */*\r\n").unwrap();
+        let round_trip =
+            HttpRequest::from_pseudo_headers(&request.to_pseudo_headers())
+                .unwrap();
+        assert!(request.semantically_eq(&round_trip));
+    }
+
+    #[test]
+    fn http_request_query_params() {
+        let request = HttpRequest::new(
+            "GET /search?q=rust+http&lang=en%2Dus&flag&&empty= HTTP/1.1\r\n\
+             Host: rsproxy.com\r\n").unwrap();
+        assert_eq!(vec![("q".to_string(), "rust http".to_string()),
+                        ("lang".to_string(), "en-us".to_string()),
+                        ("flag".to_string(), "".to_string()),
+                        ("empty".to_string(), "".to_string())],
+                   request.query_params());
+        let request = HttpRequest::new("GET /search HTTP/1.0\r\n").unwrap();
+        assert!(request.query_params().is_empty());
+    }
+
+    #[test]
+    fn http_request_decoded_path() {
+        fn decoded_path(uri: &str) -> Result<String, HttpParseError> {
+            HttpRequest::new(&format!("GET {} HTTP/1.0\r\n", uri))
+                .unwrap()
+                .decoded_path()
+        }
+        assert_eq!(Ok("/some path/caf\u{e9}".to_string()),
+                   decoded_path("/some%20path/caf%C3%A9?q=a%20b"));
+        assert_eq!(Ok("/a+b".to_string()), decoded_path("/a+b"));
+        assert_eq!(Err(HttpParseError::InvalidPercentEncoding(
+                       "/bad%2".to_string())),
+                   decoded_path("/bad%2"));
+        assert_eq!(Err(HttpParseError::InvalidPercentEncoding(
+                       "/bad%zz".to_string())),
+                   decoded_path("/bad%zz"));
+        assert_eq!(Err(HttpParseError::InvalidUtf8(1)), decoded_path("/%ff"));
+        let request = HttpRequest::new("GET /a%20b HTTP/1.0\r\n").unwrap();
+        assert_eq!("/a%20b", request.uri());
+    }
+
+    #[test]
+    fn http_request_parse_request_line() {
+        assert_eq!(Ok((HttpMethod::Get,
+                       "/index.html".to_string(),
+                       HttpVersion::Http11)),
+                   parse_request_line("GET /index.html HTTP/1.1"));
+        assert_eq!(Ok((HttpMethod::Extension("PURGE".to_string()),
+                       "*".to_string(),
+                       HttpVersion::Http10)),
+                   parse_request_line("PURGE * HTTP/1.0\r\n"));
+        assert_eq!(Ok((HttpMethod::Get, "/".to_string(), HttpVersion::Http09)),
+                   parse_request_line("GET /"));
+        assert_eq!(Err(HttpParseError::MalformedRequestLine(
+                       "GET  / HTTP/1.1".to_string())),
+                   parse_request_line("GET  / HTTP/1.1"));
+        assert_eq!(Err(HttpParseError::MissingUri),
+                   parse_request_line("GET HTTP/1.1"));
+    }
+
+    #[test]
+    fn http_request_normalized_path() {
+        fn normalized_path(uri: &str) -> String {
+            HttpRequest::new(&format!("GET {} HTTP/1.0\r\n", uri))
+                .unwrap()
+                .normalized_path()
+        }
+        assert_eq!("/a/g", normalized_path("/a/b/c/./../../g"));
+        assert_eq!("/b", normalized_path("/a/../../b"));
+        assert_eq!("/", normalized_path("/../.."));
+        assert_eq!("/a/b/", normalized_path("//a//b//?q=/../x"));
+        assert_eq!("/a/", normalized_path("/a/b/.."));
+        assert_eq!("/a/", normalized_path("/a/."));
+        assert_eq!("/etc", normalized_path("/static/%2e%2E/%2E./etc"));
+        assert_eq!("/..a/b.", normalized_path("/..a/b."));
+        assert_eq!("/", normalized_path("/"));
+        assert_eq!("/x", normalized_path("http://rsproxy.com/a/../x?y"));
+        assert_eq!("/", normalized_path("http://rsproxy.com"));
+        assert_eq!("*", normalized_path("*"));
+        assert_eq!("urn:a:../b", normalized_path("urn:a:../b"));
+    }
+
+    #[test]
+    fn http_request_invalid_uri() {
+        assert_eq!(Err(HttpParseError::MissingUri),
+                   HttpRequest::new("GET  HTTP/1.1\r\n"));
+        assert_eq!(Err(HttpParseError::MissingUri),
+                   HttpRequest::new("GET HTTP/1.1\r\n"));
+        fn invalid_uri(token: &str, index: usize) -> HttpParseError {
+            HttpParseError::InvalidUri { token: token.to_string(), index }
+        }
+        assert_eq!(Err(invalid_uri("index.html", 4)),
+                   HttpRequest::new("GET index.html HTTP/1.1\r\n"));
+        assert_eq!(Err(invalid_uri("**", 8)),
+                   HttpRequest::new("OPTIONS ** HTTP/1.1\r\n"));
+        assert_eq!(Err(HttpSyntaxError::InvalidUri(4)),
+                   HttpRequestRef::new("GET 1http://a HTTP/1.1\r\n"));
+        assert!(HttpRequest::new("GET urn:isbn:0451450523 HTTP/1.0\r\n")
+                    .is_ok());
+        assert!(HttpRequest::new("CONNECT 10.0.0.1:443 HTTP/1.0\r\n")
+                    .is_ok());
+    }
+
+    #[test]
+    fn http_request_invalid_method() {
+        let error = HttpRequest::new("G\"T / HTTP/1.1\r\n").unwrap_err();
+        assert_eq!(HttpParseError::InvalidMethod { token: "G\"T".to_string(),
+                                                   index: 0 },
+                   error);
+        assert_eq!("Invalid method \"G\\\"T\" at index 0", error.to_string());
+        assert_eq!(Err(HttpParseError::InvalidMethod {
+                       token: "[GET]".to_string(),
+                       index: 2
+                   }),
+                   HttpRequest::new_lenient("  [GET] / HTTP/1.1\r\n"));
+        assert_eq!("Invalid uri \"a\" at index 4",
+                   HttpRequest::new("GET a HTTP/1.1\r\n")
+                       .unwrap_err()
+                       .to_string());
+    }
+
+    #[test]
+    fn http_request_path_and_query() {
+        let request = HttpRequest::new("GET /a/b?x=1&y=2 HTTP/1.0\r\n")
+            .unwrap();
+        assert_eq!("/a/b", request.path());
+        assert_eq!(Some("x=1&y=2"), request.query());
+        let request = HttpRequest::new("GET /a/b HTTP/1.0\r\n").unwrap();
+        assert_eq!("/a/b", request.path());
+        assert_eq!(None, request.query());
+        let request = HttpRequest::new("GET /a? HTTP/1.0\r\n").unwrap();
+        assert_eq!("/a", request.path());
+        assert_eq!(Some(""), request.query());
+    }
+
+    #[test]
+    fn http_request_absolute_uri() {
+        fn absolute_uri(uri: &str) -> Option<(String, String)> {
+            HttpRequest::new(&format!("GET {} HTTP/1.0\r\n", uri))
+                .unwrap()
+                .absolute_uri()
+        }
+        assert_eq!(Some(("example.com".to_string(), "/path?q=1".to_string())),
+                   absolute_uri("http://example.com/path?q=1"));
+        assert_eq!(Some(("example.com:8080".to_string(), "/".to_string())),
+                   absolute_uri("https://user:pw@example.com:8080"));
+        assert_eq!(Some(("example.com".to_string(), "/?q=1".to_string())),
+                   absolute_uri("http://example.com?q=1"));
+        assert_eq!(None, absolute_uri("/path"));
+        assert_eq!(None, absolute_uri("/redirect?to=http://example.com"));
+        assert_eq!(None, absolute_uri("http:///path"));
+    }
+
+    #[test]
+    fn http_request_effective_uri() {
+        fn effective_uri(request: &str) -> Option<String> {
+            HttpRequest::new(request).unwrap().effective_uri("http")
+        }
+        assert_eq!(Some("http://rsproxy.com:8080/pub/WWW/?q=1".to_string()),
+                   effective_uri("GET /pub/WWW/?q=1 HTTP/1.1\r\n\
+                                  Host: rsproxy.com:8080\r\n"));
+        assert_eq!(Some("https://example.org/a".to_string()),
+                   effective_uri("GET https://example.org/a HTTP/1.1\r\n\
+                                  Host: rsproxy.com\r\n"));
+        assert_eq!(Some("http://rsproxy.com".to_string()),
+                   effective_uri("OPTIONS * HTTP/1.1\r\n\
+                                  Host: rsproxy.com\r\n"));
+        assert_eq!(Some("http://rsproxy.com:443".to_string()),
+                   effective_uri("CONNECT rsproxy.com:443 HTTP/1.0\r\n"));
+        assert_eq!(Some("urn:isbn:0451450523".to_string()),
+                   effective_uri("GET urn:isbn:0451450523 HTTP/1.0\r\n"));
+        assert_eq!(None, effective_uri("GET / HTTP/1.0\r\n"));
+        assert_eq!(None, effective_uri("GET / HTTP/1.1\r\nHost:\r\n"));
+        assert_eq!(Some("https://rsproxy.com/".to_string()),
+                   HttpRequest::new("GET / HTTP/1.1\r\nHost: rsproxy.com\r\n")
+                       .unwrap()
+                       .effective_uri("https"));
+    }
+
+    #[test]
+    fn http_request_with_body() {
+        let request = HttpRequest::from_bytes_with_body(
+            b"POST /submit HTTP/1.1\r\n\
+              Host: rsproxy.com\r\n\
+              Content-Length: 11\r\n\
+              \r\n\
+              hello world").unwrap();
+        assert_eq!(&HttpMethod::Post, request.method());
+        assert_eq!(2, request.headers().len());
+        assert_eq!(b"hello world", request.body());
+
+        let request = HttpRequest::from_bytes_with_body(
+            b"GET / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n").unwrap();
+        assert!(request.body().is_empty());
+    }
+
+    #[test]
+    fn http_request_with_short_body() {
+        assert_eq!(Err(HttpParseError::IncompleteBody {
+                       expected:  11,
+                       available: 5
+                   }),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\nContent-Length: 11\r\n\r\nhello"));
+        assert_eq!(Err(HttpParseError::InvalidContentLength("-1".to_string())),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\nContent-Length: -1\r\n\r\n"));
+    }
+
+    #[test]
+    fn parse_request_never_panics() {
+        // A fixed seed keeps this deterministic; the fuzz target explores the
+        // input space far more thoroughly.
+        let seeds: [&[u8]; 3] = [
+            b"GET / HTTP/1.1\r\nHost: a\r\nContent-Length: 3\r\n\r\nabc",
+            b"POST http://u@a:1/x?y=%20 HTTP/1.0\r\n\
+              Transfer-Encoding: chunked\r\n folded\r\n\r\n0\r\n\r\n",
+            b"CONNECT [::1]:80 HTTP/1.1\r\nContent-Length: 1, 1\r\n\r\n"
+        ];
+        let alphabet = b"\r\n \t:;,=-/?%\"*@[]0129afGHTP\x00\x7f\xc3\xa9\xff";
+        let mut state: u32 = 0x2545_f491;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize
+        };
+        for _ in 0 .. 20_000 {
+            let mut input = seeds[next() % seeds.len()].to_vec();
+            for _ in 0 .. next() % 8 {
+                let i = next() % (input.len() + 1);
+                let b = alphabet[next() % alphabet.len()];
+                match next() % 3 {
+                    0                    => input.insert(i, b),
+                    1 if i < input.len() => { input.remove(i); },
+                    _ if i < input.len() => input[i] = b,
+                    _                    => input.push(b)
+                }
+            }
+            let _ = parse_request(&input);
+        }
+        assert!(parse_request(b"").is_err());
+        assert!(parse_request(b"\r\n\r\n").is_err());
+        assert!(parse_request(b"\xff\xfe").is_err());
+    }
+
+    #[test]
+    fn parse_pipelined_requests() {
+        let bytes = b"GET /a HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n\
+                      POST /b HTTP/1.1\r\nHost: rsproxy.com\r\n\
+                      Content-Length: 5\r\n\r\nhello\
+                      PUT /c HTTP/1.1\r\nHost: rsproxy.com\r\n\
+                      Transfer-Encoding: chunked\r\n\r\n\
+                      2\r\nhi\r\n0\r\n\r\n\
+                      GET /d HTTP/1.1\r\nHost: rsp";
+        let (requests, consumed) = parse_requests(bytes).unwrap();
+        assert_eq!(vec!["/a", "/b", "/c"],
+                   requests.iter().map(|r| r.uri()).collect::<Vec<_>>());
+        assert_eq!(b"hello", requests[1].body());
+        assert_eq!(b"2\r\nhi\r\n0\r\n\r\n", requests[2].body());
+        assert_eq!(b"GET /d HTTP/1.1\r\nHost: rsp", &bytes[consumed ..]);
+
+        for partial in &[&b"POST / HTTP/1.1\r\nHost: a\r\n\
+                            Content-Length: 5\r\n\r\nhel"[..],
+                         &b"POST / HTTP/1.1\r\nHost: a\r\n\
+                            Transfer-Encoding: chunked\r\n\r\n2\r\nhi"[..]] {
+            assert_eq!(Ok((Vec::new(), 0)), parse_requests(partial));
+        }
+        assert_eq!(Ok((Vec::new(), 0)), parse_requests(b""));
+        assert_eq!(Err(HttpParseError::ChunkedNotFinal),
+                   parse_requests(b"POST /a HTTP/1.1\r\nHost: a\r\n\
+                                    Transfer-Encoding: gzip\r\n\r\n\
+                                    GET /smuggled HTTP/1.1\r\n\
+                                    Host: a\r\n\r\n"));
+
+        // The request before a malformed one is returned, and the error comes
+        // back once the buffer starts at the malformed request.
+        let bytes = b"GET /a HTTP/1.1\r\nHost: a\r\n\r\n\
+                      GET /b HTTP/1.1\r\n\r\n\
+                      GET /c HTTP/1.1\r\nHost: a\r\n\r\n";
+        let (requests, consumed) = parse_requests(bytes).unwrap();
+        assert_eq!(vec!["/a"],
+                   requests.iter().map(|r| r.uri()).collect::<Vec<_>>());
+        assert_eq!(b"GET /b HTTP/1.1\r\n\r\nGET /c HTTP/1.1\r\nHost: a\r\n\r\n",
+                   &bytes[consumed ..]);
+        assert_eq!(Err(HttpParseError::MissingHost),
+                   parse_requests(&bytes[consumed ..]));
+    }
+
+    #[test]
+    fn chunked_body_decoding() {
+        assert_eq!(Ok(b"Wikipedia in\r\n\r\nchunks.".to_vec()),
+                   decode_chunked(b"4\r\nWiki\r\n\
+                                    5;name=value\r\npedia\r\n\
+                                    E\r\n in\r\n\r\nchunks.\r\n\
+                                    0\r\n\
+                                    \r\n"));
+        assert_eq!(Ok(Vec::new()), decode_chunked(b"0\r\n\r\n"));
+    }
+
+    #[test]
+    fn chunked_body_trailers() {
+        let chunked = decode_chunked_with_trailers(
+            b"4\r\nWiki\r\n\
+              0\r\n\
+              Expires: Wed, 21 Oct 2015 07:28:00 GMT\r\n\
+              x-checksum: abc\r\n\
+              \r\n").unwrap();
+        assert_eq!(b"Wiki", chunked.body());
+        assert_eq!(vec!["Expires: Wed, 21 Oct 2015 07:28:00 GMT",
+                        "x-checksum: abc"],
+                   chunked.trailers()
+                          .iter()
+                          .map(|h| h.to_string())
+                          .collect::<Vec<_>>());
+        assert!(decode_chunked_with_trailers(b"0\r\n\r\n")
+                    .unwrap()
+                    .trailers()
+                    .is_empty());
+        assert_eq!(Err(HttpParseError::ForbiddenTrailer(
+                       "content-length".to_string())),
+                   decode_chunked_with_trailers(
+                       b"0\r\ncontent-length: 5\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::IncompleteChunk),
+                   decode_chunked(b"0\r\nX-Checksum: abc\r\n"));
+        assert_eq!(Err(HttpParseError::MalformedHeader(" folded".to_string())),
+                   decode_chunked(b"0\r\nA: b\r\n folded\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::MissingHeaderColon("A".to_string())),
+                   decode_chunked(b"0\r\nA\r\n\r\n"));
+    }
+
+    #[test]
+    fn chunked_body_malformed() {
+        assert_eq!(Err(HttpParseError::MalformedChunkSize("zz".to_string())),
+                   decode_chunked(b"zz\r\ndata\r\n0\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::MalformedChunkSize("".to_string())),
+                   decode_chunked(b"\r\ndata\r\n0\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::MalformedChunk),
+                   decode_chunked(b"2\r\ndata\r\n0\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::IncompleteChunk),
+                   decode_chunked(b"8\r\ndata\r\n"));
+        assert_eq!(Err(HttpParseError::IncompleteChunk),
+                   decode_chunked(b"4\r\ndata\r\n"));
+        assert!(decode_chunked(b"ffffffffffffffffff\r\n").is_err());
+    }
+
+    #[test]
+    fn http_request_host_port() {
+        fn host_port(host: &str) -> Option<(String, Option<u16>)> {
+            HttpRequest::new(&format!("GET / HTTP/1.1\r\nHost: {}\r\n", host))
+                .unwrap()
+                .host_port()
+        }
+        assert_eq!(Some(("example.com".to_string(), Some(8080))),
+                   host_port("example.com:8080"));
+        assert_eq!(Some(("example.com".to_string(), None)),
+                   host_port("example.com"));
+        assert_eq!(Some(("example.com".to_string(), None)),
+                   host_port("example.com:http"));
+        assert_eq!(Some(("[::1]".to_string(), Some(443))),
+                   host_port("[::1]:443"));
+        assert_eq!(Some(("[::1]".to_string(), None)), host_port("[::1]"));
+        assert_eq!(None, HttpRequest::new("GET / HTTP/1.0\r\n")
+                             .unwrap()
+                             .host_port());
+    }
+
+    #[test]
+    fn http_request_builder() {
+        let request = HttpRequestBuilder::new()
+            .method(HttpMethod::Post)
+            .uri("/submit")
+            .version(HttpVersion::Http10)
+            .header(HttpHeaderName::Host, "rsproxy.com")
+            .header(HttpHeaderName::Custom("X-Trace".to_string()), "abc")
+            .build();
+        assert_eq!("POST /submit HTTP/1.0\r\n\
+                    Host: rsproxy.com\r\n\
+                    X-Trace: abc\r\n\
+                    \r\n",
+                   request.to_string());
+        assert_eq!(request, HttpRequest::new(&request.to_string()).unwrap());
+
+        let request = HttpRequestBuilder::new().build();
+        assert_eq!("GET / HTTP/1.1\r\n\r\n", request.to_string());
+    }
+
+    #[test]
+    fn request_parser_partial_buffers() {
+        let mut parser = RequestParser::new();
+        assert_eq!(ParseProgress::NeedMore, parser.feed(b"GET /pa"));
+        assert_eq!(ParseProgress::NeedMore,
+                   parser.feed(b"th HTTP/1.1\r\nHost: rsproxy.com\r"));
+        assert_eq!(ParseProgress::NeedMore, parser.feed(b"\n\r"));
+        match parser.feed(b"\n") {
+            ParseProgress::Complete(request) => {
+                assert_eq!("/path", request.uri());
+                assert_eq!(Some("rsproxy.com"), request.header_str("Host"));
+            },
+            progress => panic!("Unexpected progress: {:?}", progress)
+        }
+    }
+
+    #[test]
+    fn request_parser_header_len() {
+        let head = b"POST / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n";
+        let mut parser = RequestParser::new();
+        assert_eq!(ParseProgress::NeedMore, parser.feed(&head[.. 20]));
+        assert_eq!(0, parser.header_len());
+        let mut buffer = head[20 ..].to_vec();
+        buffer.extend_from_slice(b"body");
+        match parser.feed(&buffer) {
+            ParseProgress::Complete(_) => {},
+            progress => panic!("Unexpected progress: {:?}", progress)
+        }
+        assert_eq!(head.len(), parser.header_len());
+        assert_eq!(b"body", &buffer[parser.header_len() - 20 ..]);
+    }
+
+    #[test]
+    fn request_parser_error() {
+        let mut parser = RequestParser::new();
+        assert_eq!(ParseProgress::Error(
+                       HttpParseError::MalformedRequestLine("GET".to_string())),
+                   parser.feed(b"GET\r\n\r\n"));
+        assert_eq!(ParseProgress::Error(HttpParseError::ParserFinished),
+                   parser.feed(b"GET / HTTP/1.0\r\n\r\n"));
+    }
+
+    #[test]
+    fn request_parser_single_use() {
+        let head = b"GET /a HTTP/1.0\r\n\r\n";
+        let mut parser = RequestParser::new();
+        match parser.feed(head) {
+            ParseProgress::Complete(request) => assert_eq!("/a", request.uri()),
+            progress => panic!("Unexpected progress: {:?}", progress)
+        }
+        assert_eq!(ParseProgress::Error(HttpParseError::ParserFinished),
+                   parser.feed(b"GET /b HTTP/1.0\r\n\r\n"));
+        assert_eq!(head.len(), parser.header_len());
+    }
+
+    #[test]
+    fn request_parser_limits() {
+        let limits = ParseLimits { max_header_bytes: 64, ..Default::default() };
+        let mut parser = RequestParser::with_limits(limits.clone());
+        assert_eq!(ParseProgress::NeedMore,
+                   parser.feed(b"GET / HTTP/1.0\r\nX-Pad: "));
+        assert_eq!(ParseProgress::Error(HttpParseError::HeaderBlockTooLarge),
+                   parser.feed(&[b'a'; 64]));
+        assert_eq!(ParseProgress::Error(HttpParseError::ParserFinished),
+                   parser.feed(b"\r\n\r\n"));
+
+        let limits = ParseLimits { max_header_count: 1, ..Default::default() };
+        let mut parser = RequestParser::with_limits(limits);
+        assert_eq!(ParseProgress::Error(HttpParseError::TooManyHeaders),
+                   parser.feed(b"GET / HTTP/1.0\r\nA: 1\r\nB: 2\r\n\r\n"));
+    }
+
+    #[test]
+    fn http_request_stops_at_body() {
+        let request_str = "POST /submit HTTP/1.1\r\n\
+                           Host: rsproxy.com\r\n\
+                           Content-Length: 25\r\n\
+                           \r\n\
+                           not-a-header\r\n\
+                           X-Fake: 1\r\n";
+        let request = HttpRequest::new(request_str).unwrap();
+        assert_eq!(2, request.headers().len());
+        assert_eq!(None, request.header_str("X-Fake"));
+
+        let request = HttpRequest::from_bytes(
+            b"POST / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n\xff\xfe").unwrap();
+        assert_eq!(1, request.headers().len());
+    }
+
+    #[test]
+    fn http_request_repeated_headers() {
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Cookie: a=1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Cookie: b=2\r\n").unwrap();
+        assert_eq!(vec!["a=1", "b=2"],
+                   request.header_values(&HttpHeaderName::Cookie));
+        assert_eq!("a=1",
+                   request.header(&HttpHeaderName::Cookie).unwrap().value());
+        assert!(request.header_values(&HttpHeaderName::Accept).is_empty());
+    }
+
+    #[test]
+    fn http_request_combined_header() {
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Accept: text/html\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Accept: application/json;q=0.9\r\n")
+            .unwrap();
+        assert_eq!(Some("text/html, application/json;q=0.9".to_string()),
+                   request.combined_header(&HttpHeaderName::Accept));
+        assert_eq!(Some("rsproxy.com".to_string()),
+                   request.combined_header(&HttpHeaderName::Host));
+        assert_eq!(None, request.combined_header(&HttpHeaderName::Cookie));
+    }
+
+    #[test]
+    fn http_request_accepted_media_types() {
+        let request = HttpRequest::new(
+            "GET / HTTP/1.1\r\n\
+             Host: rsproxy.com\r\n\
+             Accept: audio/*; q=0.2, audio/basic, text/html;level=1;q=0.5\r\n\
+             Accept: text/plain; q=bad, */*;Q=0\r\n").unwrap();
+        assert_eq!(vec![("audio/basic".to_string(), 1.0),
+                        ("text/html;level=1".to_string(), 0.5),
+                        ("audio/*".to_string(), 0.2),
+                        ("*/*".to_string(), 0.0)],
+                   request.accepted_media_types());
+        let request = HttpRequest::new("GET / HTTP/1.0\r\n").unwrap();
+        assert!(request.accepted_media_types().is_empty());
+    }
+
+    #[test]
+    fn http_request_accepted_languages() {
+        let request = HttpRequest::new(
+            "GET / HTTP/1.1\r\n\
+             Host: rsproxy.com\r\n\
+             Accept-Language: en;q=0.7, *;q=0.1, da\r\n\
+             accept-language: en-GB;q=0.8, fr;q=x\r\n").unwrap();
+        assert_eq!(vec![("da".to_string(), 1.0),
+                        ("en-GB".to_string(), 0.8),
+                        ("en".to_string(), 0.7),
+                        ("*".to_string(), 0.1)],
+                   request.accepted_languages());
+        let request = HttpRequest::new("GET / HTTP/1.0\r\n").unwrap();
+        assert!(request.accepted_languages().is_empty());
+    }
+
+    #[test]
+    fn http_request_content_type() {
+        fn content_type(
+            value: &str
+        ) -> Option<(String, Vec<(String, String)>)> {
+            HttpRequest::new(&format!("POST / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n\
+                                       Content-Type: {}\r\n", value))
+                .unwrap()
+                .content_type()
+        }
+        assert_eq!(Some(("text/html".to_string(),
+                         vec![("charset".to_string(), "utf-8".to_string())])),
+                   content_type("text/html; charset=utf-8"));
+        assert_eq!(Some(("multipart/form-data".to_string(),
+                         vec![("boundary".to_string(), "a b\"c".to_string()),
+                              ("x".to_string(), "Y".to_string())])),
+                   content_type("Multipart/Form-Data; \
+                                 BOUNDARY=\"a b\\\"c\"; noise; x=Y;"));
+        assert_eq!(Some(("multipart/mixed".to_string(),
+                         vec![("boundary".to_string(), "a;b".to_string()),
+                              ("x".to_string(), "y".to_string())])),
+                   content_type("multipart/mixed; boundary=\"a;b\"; x=y"));
+        assert_eq!(Some(("application/json".to_string(), vec![])),
+                   content_type("application/json"));
+        assert_eq!(None, content_type("; charset=utf-8"));
+        assert_eq!(None,
+                   HttpRequest::new("POST / HTTP/1.0\r\n")
+                       .unwrap()
+                       .content_type());
+    }
+
+    #[test]
+    fn http_request_multipart_boundary() {
+        fn boundary(content_type: &str) -> Option<String> {
+            HttpRequest::new(&format!("POST / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n\
+                                       Content-Type: {}\r\n", content_type))
+                .unwrap()
+                .multipart_boundary()
+        }
+        assert_eq!(Some("----WebKitFormBoundary7MA4YWxk".to_string()),
+                   boundary("multipart/form-data; \
+                             boundary=----WebKitFormBoundary7MA4YWxk"));
+        assert_eq!(Some("simple boundary".to_string()),
+                   boundary("multipart/mixed; Boundary=\"simple boundary\""));
+        assert_eq!(Some("a;b".to_string()),
+                   boundary("multipart/form-data; boundary=\"a;b\""));
+        assert_eq!(None, boundary("multipart/form-data"));
+        assert_eq!(None, boundary("text/plain; boundary=abc"));
+    }
+
+    #[test]
+    fn http_request_accepts_encoding() {
+        fn accepts(accept_encoding: Option<&str>, encoding: &str) -> bool {
+            let header = accept_encoding.map_or(String::new(), |value| {
+                format!("Accept-Encoding: {}\r\n", value)
+            });
+            HttpRequest::new(&format!("GET / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n{}", header))
+                .unwrap()
+                .accepts_encoding(encoding)
+        }
+        assert!(accepts(None, "gzip"));
+        assert!(accepts(Some("gzip, deflate"), "gzip"));
+        assert!(accepts(Some("GZIP;q=0.5"), "gzip"));
+        assert!(!accepts(Some("gzip;q=0, deflate"), "gzip"));
+        assert!(!accepts(Some("deflate"), "gzip"));
+        assert!(accepts(Some("deflate"), "identity"));
+        assert!(accepts(Some("*"), "br"));
+        assert!(!accepts(Some("*;q=0"), "br"));
+        assert!(!accepts(Some("*;q=0"), "identity"));
+        assert!(accepts(Some("gzip;q=1, *;q=0"), "gzip"));
+        assert!(!accepts(Some("identity;q=0"), "identity"));
+    }
+
+    #[test]
+    fn http_request_cookies() {
+        let request = HttpRequest::new(
+            "GET / HTTP/1.1\r\n\
+             Host: rsproxy.com\r\n\
+             Cookie: session=abc123; token=a=b==; flag;\r\n\
+             Cookie: theme=dark\r\n").unwrap();
+        assert_eq!(vec![("session".to_string(), "abc123".to_string()),
+                        ("token".to_string(), "a=b==".to_string()),
+                        ("flag".to_string(), "".to_string()),
+                        ("theme".to_string(), "dark".to_string())],
+                   request.cookies());
+        let request = HttpRequest::new("GET / HTTP/1.0\r\n").unwrap();
+        assert!(request.cookies().is_empty());
+    }
+
+    #[test]
+    fn http_request_basic_auth() {
+        fn basic_auth(
+            authorization: &str
+        ) -> Result<Option<(String, String)>, HttpParseError> {
+            HttpRequest::new(&format!("GET / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n\
+                                       Authorization: {}\r\n",
+                                      authorization))
+                .unwrap()
+                .basic_auth()
+        }
+        assert_eq!(Ok(Some(("Aladdin".to_string(),
+                            "open sesame".to_string()))),
+                   basic_auth("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="));
+        assert_eq!(Ok(Some(("user".to_string(), "p:w".to_string()))),
+                   basic_auth("basic dXNlcjpwOnc="));
+        assert_eq!(Ok(Some(("a".to_string(), "".to_string()))),
+                   basic_auth("Basic YTo="));
+        assert_eq!(Ok(None), basic_auth("Bearer QWxhZGRpbjpvcGVuIHNlc2FtZQ=="));
+        assert_eq!(Err(HttpParseError::InvalidBase64("QWxh!".to_string())),
+                   basic_auth("Basic QWxh!"));
+        assert_eq!(Err(HttpParseError::InvalidBase64("QQ==QQ==".to_string())),
+                   basic_auth("Basic QQ==QQ=="));
+        assert_eq!(Err(HttpParseError::MalformedCredentials),
+                   basic_auth("Basic QWxhZGRpbg=="));
+        assert_eq!(Ok(None),
+                   HttpRequest::new("GET / HTTP/1.0\r\n")
+                       .unwrap()
+                       .basic_auth());
+    }
+
+    #[test]
+    fn http_request_keep_alive() {
+        fn keep_alive(version: &str, connection: Option<&str>) -> bool {
+            let header = connection.map_or(String::new(), |value| {
+                format!("Connection: {}\r\n", value)
+            });
+            HttpRequest::new(&format!("GET / {}\r\nHost: rsproxy.com\r\n{}",
+                                      version, header))
+                .unwrap()
+                .is_keep_alive()
+        }
+        assert!(keep_alive("HTTP/1.1", None));
+        assert!(keep_alive("HTTP/1.1", Some("keep-alive")));
+        assert!(!keep_alive("HTTP/1.1", Some("close")));
+        assert!(!keep_alive("HTTP/1.1", Some("Upgrade, Close")));
+        assert!(!keep_alive("HTTP/1.0", None));
+        assert!(keep_alive("HTTP/1.0", Some("Keep-Alive")));
+        assert!(!keep_alive("HTTP/1.0", Some("close")));
+    }
+
+    #[test]
+    fn http_request_byte_ranges() {
+        fn byte_ranges(
+            range: &str
+        ) -> Result<Option<Vec<ByteRange>>, HttpParseError> {
+            HttpRequest::new(&format!("GET / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n\
+                                       Range: {}\r\n", range))
+                .unwrap()
+                .byte_ranges()
+        }
+        assert_eq!(Ok(Some(vec![(Some(0), Some(499)),
+                                (Some(500), Some(999))])),
+                   byte_ranges("bytes=0-499, 500-999"));
+        assert_eq!(Ok(Some(vec![(None, Some(500))])),
+                   byte_ranges("bytes=-500"));
+        assert_eq!(Ok(Some(vec![(Some(9500), None)])),
+                   byte_ranges("Bytes=9500-"));
+        assert_eq!(Ok(Some(vec![(Some(0), Some(1)), (Some(5), None)])),
+                   byte_ranges("bytes=0-1,, 5-,"));
+        assert_eq!(Err(HttpParseError::MalformedRange(
+                       "items=0-5".to_string())),
+                   byte_ranges("items=0-5"));
+        for range in &["bytes=-", "bytes=5-1", "bytes=a-b", "bytes=1",
+                       "bytes=", "bytes= ,", "bytes=+1-2", "0-499"] {
+            assert_eq!(Err(HttpParseError::MalformedRange(range.to_string())),
+                       byte_ranges(range));
+        }
+        assert_eq!(Ok(None),
+                   HttpRequest::new("GET / HTTP/1.0\r\n")
+                       .unwrap()
+                       .byte_ranges());
+    }
+
+    #[test]
+    fn http_request_is_conditional() {
+        fn is_conditional(header: &str) -> bool {
+            HttpRequest::new(&format!("GET / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n{}", header))
+                .unwrap()
+                .is_conditional()
+        }
+        assert!(is_conditional("If-Modified-Since: \
+                                Sat, 29 Oct 1994 19:43:31 GMT\r\n"));
+        assert!(is_conditional("if-none-match: \"xyzzy\"\r\n"));
+        assert!(is_conditional("If-Match: *\r\n"));
+        assert!(is_conditional("IF-UNMODIFIED-SINCE: \
+                                Sat, 29 Oct 1994 19:43:31 GMT\r\n"));
+        assert!(!is_conditional(""));
+        assert!(!is_conditional("If-Range: \"xyzzy\"\r\n"));
+    }
+
+    #[test]
+    fn http_request_if_none_match() {
+        fn if_none_match(value: &str) -> Vec<EntityTag> {
+            HttpRequest::new(&format!("GET / HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n\
+                                       If-None-Match: {}\r\n", value))
+                .unwrap()
+                .if_none_match()
+        }
+        fn tag(weak: bool, opaque: &str) -> EntityTag {
+            EntityTag::Tag { weak, opaque: opaque.to_string() }
+        }
+        assert_eq!(vec![tag(false, "xyzzy")], if_none_match("\"xyzzy\""));
+        assert_eq!(vec![tag(false, "xyzzy"), tag(true, "r2d2xxxx"),
+                        tag(false, "c3piozzzz")],
+                   if_none_match("\"xyzzy\", W/\"r2d2xxxx\", \"c3piozzzz\","));
+        assert_eq!(vec![EntityTag::Any], if_none_match("*"));
+        assert_eq!(vec![tag(false, "")], if_none_match("\"\""));
+        assert_eq!(vec![tag(false, "*"), tag(true, "x"), tag(false, "W/x"),
+                        tag(false, "a,b")],
+                   if_none_match("\"*\", W/\"x\", \"W/x\", \"a,b\""));
+        assert_eq!(vec![tag(false, "a")], if_none_match("xyzzy, W/x, \"a\""));
+        assert!(HttpRequest::new("GET / HTTP/1.0\r\n")
+                    .unwrap()
+                    .if_none_match()
+                    .is_empty());
+    }
+
+    #[test]
+    fn http_request_upgrade_protocol() {
+        let request = HttpRequest::new("GET /chat HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Upgrade: websocket\r\n\
+                                        Connection: keep-alive, Upgrade\r\n")
+            .unwrap();
+        assert_eq!(Some("websocket"), request.upgrade_protocol());
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Connection: upgrade\r\n\
+                                        Upgrade: h2c, websocket\r\n")
+            .unwrap();
+        assert_eq!(Some("h2c"), request.upgrade_protocol());
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Upgrade: websocket\r\n").unwrap();
+        assert_eq!(None, request.upgrade_protocol());
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Connection: Upgrade\r\n").unwrap();
+        assert_eq!(None, request.upgrade_protocol());
+    }
+
+    #[test]
+    fn http_request_websocket_upgrade() {
+        let handshake = |headers: &str| {
+            HttpRequest::new(&format!("GET /chat HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n{}", headers))
+                .unwrap()
+        };
+        let request = handshake("Upgrade: h2c, WebSocket\r\n\
+                                 Connection: keep-alive, Upgrade\r\n\
+                                 Sec-WebSocket-Key: dGhlIHNhbXBsZQ==\r\n");
+        assert!(request.is_websocket_upgrade());
+        assert_eq!(Some("dGhlIHNhbXBsZQ=="), request.websocket_key());
+        let request = handshake("Upgrade: websocket\r\n\
+                                 Connection: Upgrade\r\n");
+        assert!(!request.is_websocket_upgrade());
+        assert_eq!(None, request.websocket_key());
+        let request = handshake("Upgrade: h2c\r\n\
+                                 Connection: Upgrade\r\n\
+                                 Sec-WebSocket-Key: dGhlIHNhbXBsZQ==\r\n");
+        assert!(!request.is_websocket_upgrade());
+        let request = handshake("Upgrade: websocket\r\n\
+                                 Sec-WebSocket-Key: dGhlIHNhbXBsZQ==\r\n");
+        assert!(!request.is_websocket_upgrade());
+        let request = handshake("Upgrade: websocket\r\n\
+                                 Connection: Upgrade\r\n\
+                                 Sec-WebSocket-Key: \r\n");
+        assert!(!request.is_websocket_upgrade());
+    }
+
+    #[test]
+    fn http_request_has_body() {
+        fn has_body(request: &str) -> bool {
+            HttpRequest::new(request).unwrap().has_body()
+        }
+        assert!(!has_body("GET / HTTP/1.0\r\nHost: rsproxy.com\r\n"));
+        assert!(!has_body("HEAD / HTTP/1.0\r\n"));
+        assert!(!has_body("POST / HTTP/1.0\r\nContent-Length: 0\r\n"));
+        assert!(has_body("POST / HTTP/1.0\r\nContent-Length: 12\r\n"));
+        assert!(has_body("GET / HTTP/1.0\r\nContent-Length: 1\r\n"));
+        assert!(has_body("POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n"));
+        assert!(has_body("PUT / HTTP/1.0\r\n\
+                          Transfer-Encoding: gzip, Chunked\r\n"));
+        assert!(!has_body("POST / HTTP/1.0\r\nTransfer-Encoding: gzip\r\n"));
+        assert!(!has_body("POST / HTTP/1.0\r\nContent-Length: abc\r\n"));
+    }
+
+    #[test]
+    fn http_request_is_safely_retryable() {
+        fn retryable(request: &str) -> bool {
+            HttpRequest::new(request).unwrap().is_safely_retryable()
+        }
+        assert!(retryable("GET / HTTP/1.0\r\n"));
+        assert!(retryable("DELETE /item/1 HTTP/1.0\r\n"));
+        assert!(retryable("PUT /item/1 HTTP/1.0\r\nContent-Length: 0\r\n"));
+        assert!(!retryable("PUT /item/1 HTTP/1.0\r\nContent-Length: 4\r\n"));
+        assert!(!retryable("POST /orders HTTP/1.0\r\n"));
+        assert!(!retryable("PATCH /item/1 HTTP/1.0\r\n\
+                            Transfer-Encoding: chunked\r\n"));
+        assert!(retryable("PUT /item/1 HTTP/1.0\r\n\
+                           Content-Length: 4\r\n\
+                           Idempotency-Key: \"8e03978e\"\r\n"));
+        assert!(!retryable("POST /orders HTTP/1.0\r\n\
+                            Content-Length: 4\r\n\
+                            Idempotency-Key: \"8e03978e\"\r\n"));
+    }
+
+    #[test]
+    fn http_request_transfer_codings() {
+        fn transfer_codings(
+            values: &[&str]
+        ) -> Result<Vec<String>, HttpParseError> {
+            let header: String = values.iter()
+                .map(|v| format!("Transfer-Encoding: {}\r\n", v))
+                .collect();
+            HttpRequest::new(&format!("POST / HTTP/1.0\r\n{}", header))
+                .unwrap()
+                .transfer_codings()
+        }
+        assert_eq!(Ok(vec!["gzip".to_string(), "chunked".to_string()]),
+                   transfer_codings(&["GZIP, Chunked"]));
+        assert_eq!(Ok(vec!["x-custom".to_string(), "gzip".to_string(),
+                           "chunked".to_string()]),
+                   transfer_codings(&["x-custom;level=1", "gzip ,chunked"]));
+        assert_eq!(Ok(vec!["gzip".to_string()]), transfer_codings(&["gzip"]));
+        assert_eq!(Ok(Vec::new()), transfer_codings(&[]));
+        assert_eq!(Err(HttpParseError::ChunkedNotFinal),
+                   transfer_codings(&["chunked, gzip"]));
+        assert_eq!(Err(HttpParseError::ChunkedNotFinal),
+                   transfer_codings(&["chunked", "gzip"]));
+        assert_eq!(Err(HttpParseError::ChunkedNotFinal),
+                   transfer_codings(&["chunked", "chunked"]));
+
+        assert_eq!(Err(HttpParseError::ChunkedNotFinal),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\n\
+                         Transfer-Encoding: chunked, gzip\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::ChunkedNotFinal),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\n\
+                         Transfer-Encoding: gzip\r\n\r\nbody"));
+        assert!(!HttpRequest::new("POST / HTTP/1.0\r\n\
+                                   Transfer-Encoding: chunked, gzip\r\n")
+                     .unwrap()
+                     .has_body());
+    }
+
+    #[test]
+    fn http_request_expects_continue() {
+        fn expects_continue(header: &str) -> bool {
+            HttpRequest::new(&format!("PUT /upload HTTP/1.1\r\n\
+                                       Host: rsproxy.com\r\n{}", header))
+                .unwrap()
+                .expects_continue()
+        }
+        assert!(expects_continue("Expect: 100-continue\r\n"));
+        assert!(expects_continue("expect: 100-Continue\r\n"));
+        assert!(!expects_continue("Expect: 200-ok\r\n"));
+        assert!(!expects_continue(""));
+    }
+
+    #[test]
+    fn http_request_as_ref_view() {
+        let request_str = "PROPFIND /dav HTTP/1.1\r\n\
+                           host: rsproxy.com\r\n\
+                           X-Custom: a\r\n\
+                           \r\n";
+        let request = HttpRequest::new(request_str).unwrap();
+        let view = request.as_ref_view();
+        assert_eq!("PROPFIND", view.method());
+        assert_eq!("/dav", view.uri());
+        assert_eq!("HTTP/1.1", view.version());
+        assert_eq!(vec![("host", "rsproxy.com"), ("X-Custom", "a")],
+                   view.headers()
+                       .map(|h| (h.name(), h.value()))
+                       .collect::<Vec<_>>());
+        assert_eq!(Some("a"), view.header_str("x-custom"));
+        assert_eq!(HttpRequestRef::new(request_str).unwrap(), view);
+        assert_eq!(request, view.to_owned());
+    }
+
+    #[test]
+    fn http_request_cache_key() {
+        let request = HttpRequest::new("GET /a?b=2&a=1 HTTP/1.1\r\n\
+                                        Host: RSProxy.com\r\n\
+                                        Accept-Encoding: gzip,   br\r\n\
+                                        Cookie: id=1\r\n").unwrap();
+        let vary = [HttpHeaderName::Cookie, HttpHeaderName::AcceptEncoding];
+        assert_eq!("GET http://rsproxy.com/a?b=2&a=1\n\
+                    accept-encoding: gzip, br\n\
+                    cookie: id=1",
+                   request.cache_key(&vary));
+        let absolute = HttpRequest::new("GET http://rsproxy.com/a?a=1&b=2 \
+                                         HTTP/1.1\r\n\
+                                         Host: rsproxy.com\r\n\
+                                         Accept-Encoding: gzip, br\r\n\
+                                         Cookie: id=1\r\n").unwrap();
+        let reversed = [HttpHeaderName::AcceptEncoding, HttpHeaderName::Cookie,
+                        HttpHeaderName::Cookie];
+        assert!(request.cache_key(&vary) != absolute.cache_key(&reversed));
+        let sorted = CacheKeyOptions { sort_query: true };
+        assert_eq!(request.cache_key_with(&vary, &sorted),
+                   absolute.cache_key_with(&reversed, &sorted));
+        assert_eq!("GET http://rsproxy.com/a?a=1&b=2",
+                   request.cache_key_with(&[], &sorted));
+        assert_eq!("GET http://rsproxy.com/a?b=2&a=1\nx-missing: ",
+                   request.cache_key(&[HttpHeaderName::new("X-Missing")]));
+
+        let key = |request_line: &str| {
+            HttpRequest::new(&format!("{}\r\nHost: a\r\n", request_line))
+                .unwrap()
+                .cache_key(&[])
+        };
+        assert_eq!("GET http://a/x", key("GET http://a/x HTTP/1.1"));
+        assert_eq!("GET https://a/x", key("GET HTTPS://A/x HTTP/1.1"));
+        assert!(key("GET http://a/x HTTP/1.1") !=
+                key("GET https://a/x HTTP/1.1"));
+        assert_eq!(key("GET http://a/x HTTP/1.1"), key("GET /x HTTP/1.1"));
+        assert_eq!("GET http://a/x/?q=%2E",
+                   key("GET /b/../x/./?q=%2E HTTP/1.1"));
+        assert_eq!("GET http://a/x/", key("GET //b/..//x/ HTTP/1.1"));
+        assert_eq!("CONNECT a:443", key("CONNECT a:443 HTTP/1.1"));
+        assert_eq!("OPTIONS *", key("OPTIONS * HTTP/1.1"));
+    }
+
+    #[test]
+    fn http_request_semantically_eq() {
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Accept: text/html\r\n\
+                                        Accept: text/html\r\n").unwrap();
+        let reordered = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                          accept: text/html\r\n\
+                                          Host: rsproxy.com\r\n\
+                                          Accept: text/html\r\n").unwrap();
+        let fewer = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                      Host: rsproxy.com\r\n\
+                                      Accept: text/html\r\n").unwrap();
+        let other_uri = HttpRequest::new("GET /other HTTP/1.1\r\n\
+                                          Host: rsproxy.com\r\n\
+                                          Accept: text/html\r\n\
+                                          Accept: text/html\r\n").unwrap();
+        assert!(request != reordered);
+        assert!(request.semantically_eq(&reordered));
+        assert!(!request.semantically_eq(&fewer));
+        assert!(!request.semantically_eq(&other_uri));
+    }
+
+    #[test]
+    fn http_request_bare_line_feed() {
+        let request_str = "GET / HTTP/1.1\r\n\
+                           Host: rsproxy.com\n\
+                           X-Smuggled: 1\r\n\
+                           \r\n\
+                           body\nwith\nnewlines";
+        assert_eq!(Err(HttpParseError::BareLineFeed(33)),
+                   HttpRequest::new(request_str));
+        assert_eq!(Err(HttpSyntaxError::BareLineFeed(33)),
+                   HttpRequestRef::new(request_str));
+        assert_eq!(Err(HttpParseError::BareLineFeed(14)),
+                   HttpRequest::new("GET / HTTP/1.1\nHost: rsproxy.com\r\n"));
+
+        let request = HttpRequest::new_lenient(request_str).unwrap();
+        assert_eq!(Some("rsproxy.com"), request.header_str("Host"));
+        assert_eq!(Some("1"), request.header_str("X-Smuggled"));
+
+        let request = HttpRequest::new(
+            "GET / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\nbody\n").unwrap();
+        assert_eq!(1, request.headers().len());
+    }
+
+    #[test]
+    fn http_request_ambiguous_body_length() {
+        assert_eq!(Err(HttpParseError::ContentLengthWithTransferEncoding),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\n\
+                         Content-Length: 4\r\n\
+                         Transfer-Encoding: chunked\r\n\
+                         \r\n\
+                         0\r\n\r\n"));
+        assert_eq!(Err(HttpParseError::ConflictingContentLength),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\n\
+                         Content-Length: 4\r\n\
+                         Content-Length: 5\r\n\
+                         \r\n\
+                         hello"));
+        assert_eq!(Err(HttpParseError::ConflictingContentLength),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\nContent-Length: 4, 5\r\n\r\nhello"));
+        assert_eq!(Err(HttpParseError::InvalidContentLength("+5".to_string())),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\nContent-Length: +5\r\n\r\nhello"));
+        assert_eq!(Err(HttpParseError::InvalidContentLength(",".to_string())),
+                   HttpRequest::from_bytes_with_body(
+                       b"POST / HTTP/1.0\r\nContent-Length: ,\r\n\r\n"));
+        let request = HttpRequest::from_bytes_with_body(
+            b"POST / HTTP/1.0\r\nContent-Length: 5, ,5\r\n\r\nhello").unwrap();
+        assert_eq!(b"hello", request.body());
+        let request = HttpRequest::from_bytes_with_body(
+            b"POST / HTTP/1.0\r\n\
+              Content-Length: 5\r\n\
+              Content-Length: 5\r\n\
+              \r\n\
+              hello").unwrap();
+        assert_eq!(b"hello", request.body());
+    }
+
+    #[test]
+    fn parse_request_chunked_body() {
+        let chunked = b"4\r\nWiki\r\n0\r\nX-Checksum: abc\r\n\r\n";
+        let mut bytes = b"POST / HTTP/1.1\r\nHost: rsproxy.com\r\n\
+                          Transfer-Encoding: chunked\r\n\r\n".to_vec();
+        bytes.extend_from_slice(chunked);
+        let request = parse_request(&bytes).unwrap();
+        assert_eq!(chunked, request.body());
+        assert_eq!(Ok(b"Wiki".to_vec()), decode_chunked(request.body()));
+        assert_eq!(Err(HttpParseError::IncompleteChunk),
+                   parse_request(&bytes[.. bytes.len() - 2]));
+    }
+
+    #[test]
+    fn http_request_host_count() {
+        assert_eq!(Err(HttpParseError::MissingHost),
+                   HttpRequest::new("GET / HTTP/1.1\r\nAccept: */*\r\n"));
+        assert_eq!(Err(HttpParseError::DuplicateHost),
+                   HttpRequest::new("GET / HTTP/1.1\r\n\
+                                     Host: rsproxy.com\r\n\
+                                     host: evil.com\r\n"));
+        assert_eq!(Err(HttpParseError::DuplicateHost),
+                   HttpRequest::from_bytes(b"GET / HTTP/1.1\r\n\
+                                             Host: a\r\nHost: a\r\n\r\n"));
+        assert!(HttpRequest::new("GET / HTTP/1.1\r\nHost: a\r\n").is_ok());
+        assert!(HttpRequest::new("GET / HTTP/1.0\r\n").is_ok());
+        assert!(HttpRequest::new_lenient("GET / HTTP/1.1\r\n").is_ok());
+        assert!(HttpRequest::new_lenient("GET / HTTP/1.1\r\n\
+                                          Host: a\r\nHost: b\r\n").is_ok());
+    }
+
+    #[test]
+    fn http_request_leading_empty_lines() {
+        let request_str = "GET / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n";
+        let request = HttpRequest::new(request_str).unwrap();
+        for leading in &["\r\n", "\r\n\r\n\r\n"] {
+            let padded = format!("{}{}", leading, request_str);
+            assert_eq!(Ok(&request), HttpRequest::new(&padded).as_ref());
+            assert_eq!(Ok(&request),
+                       HttpRequest::from_bytes(padded.as_bytes()).as_ref());
+            assert_eq!(Ok(request.as_ref_view()), HttpRequestRef::new(&padded));
+            let mut parser = RequestParser::new();
+            assert_eq!(ParseProgress::NeedMore,
+                       parser.feed(leading.as_bytes()));
+            assert_eq!(ParseProgress::Complete(request.clone()),
+                       parser.feed(request_str.as_bytes()));
+            assert_eq!(padded.len(), parser.header_len());
+        }
+        assert_eq!(Ok(&request),
+                   HttpRequest::new_lenient(&format!("\n\r\n{}", request_str))
+                       .as_ref());
+        assert!(HttpRequest::new(&format!("\n{}", request_str)).is_err());
+
+        let too_many = format!("{}{}", "\r\n".repeat(9), request_str);
+        assert!(HttpRequest::new(&too_many).is_err());
+        assert!(HttpRequest::from_bytes(too_many.as_bytes()).is_err());
+        assert!(HttpRequest::from_bytes_with_body(too_many.as_bytes())
+                    .is_err());
+        let limits = ParseLimits {
+            max_leading_empty_lines: 9,
+            ..ParseLimits::default()
+        };
+        assert_eq!(Ok(&request),
+                   HttpRequest::new_with_limits(&too_many, &limits).as_ref());
+
+        let (requests, consumed) =
+            parse_requests(format!("{}\r\n{}\r\n", request_str, request_str)
+                               .as_bytes())
+                .unwrap();
+        assert_eq!(2, requests.len());
+        assert_eq!(2 * request_str.len() + 2, consumed);
+    }
+
+    #[test]
+    fn http_request_limits() {
+        let limits = ParseLimits {
+            max_header_count:        2,
+            max_header_line_length:  32,
+            max_header_bytes:        64,
+            max_leading_empty_lines: 1
+        };
+        let parse = |request_str: &str| {
+            HttpRequest::new_with_limits(request_str, &limits)
+        };
+        assert!(parse("GET / HTTP/1.0\r\nA: 1\r\nB: 2\r\n\r\n").is_ok());
+        assert_eq!(Err(HttpParseError::TooManyHeaders),
+                   parse("GET / HTTP/1.0\r\nA: 1\r\nB: 2\r\nC: 3\r\n"));
+        assert_eq!(Err(HttpParseError::HeaderLineTooLong),
+                   parse("GET / HTTP/1.0\r\n\
+                          X-Long: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\r\n"));
+        assert_eq!(Err(HttpParseError::HeaderBlockTooLarge),
+                   parse("GET / HTTP/1.0\r\n\
+                          X-First: aaaaaaaaaaaaaaaaaaaa\r\n\
+                          X-Second: aaaaaaaaaaaaaaaaaaa\r\n"));
+        // Content after the header block isn't counted.
+        assert!(parse("GET / HTTP/1.0\r\nA: 1\r\n\r\nB: 2\r\nC: 3\r\n\
+                       D: 4  aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+                    .is_ok());
+
+        let request_str = "GET / HTTP/1.0\r\nHost: rsproxy.com\r\n\r\n";
+        assert_eq!(HttpRequest::new(request_str),
+                   HttpRequest::new_with_limits(request_str,
+                                                &ParseLimits::default()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn http_types_hash_and_clone() {
+        use std::collections::HashSet;
+
+        let methods: HashSet<HttpMethod> =
+            vec![HttpMethod::Get, HttpMethod::Post, HttpMethod::Get]
+                .into_iter()
+                .collect();
+        assert_eq!(2, methods.len());
+        assert!(methods.contains(&HttpMethod::Post));
+
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n").unwrap();
+        let copy = request.clone();
+        assert_eq!(request, copy);
+        let mut requests = HashSet::new();
+        requests.insert(request);
+        assert!(requests.contains(&copy));
+
+        let names: HashSet<HttpHeaderName> =
+            copy.headers().iter().map(|h| h.name().clone()).collect();
+        assert!(names.contains(&HttpHeaderName::Host));
+    }
+
+    #[test]
+    fn http_request_target_forms() {
+        fn target_of(request_line: &str) -> HttpRequest {
+            HttpRequest::new(&format!("{}\r\nHost: rsproxy.com\r\n",
+                                      request_line)).unwrap()
+        }
+        assert_eq!(RequestTarget::Asterisk,
+                   target_of("OPTIONS * HTTP/1.1").target());
+        assert_eq!(RequestTarget::Origin("/where?q=now"),
+                   target_of("GET /where?q=now HTTP/1.1").target());
+        assert_eq!(RequestTarget::Absolute("http://www.example.org/pub"),
+                   target_of("GET http://www.example.org/pub HTTP/1.1").target());
+        assert_eq!(RequestTarget::Authority("www.example.com:80"),
+                   target_of("CONNECT www.example.com:80 HTTP/1.1").target());
+        assert_eq!(RequestTarget::Absolute("urn:isbn:0451450523"),
+                   target_of("GET urn:isbn:0451450523 HTTP/1.1").target());
+    }
+
+    #[test]
+    fn http_request_header_iteration() {
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        Accept: */*\r\n").unwrap();
+        let mut names = Vec::new();
+        for header in &request {
+            names.push(header.original_name());
+        }
+        assert_eq!(vec!["Host", "Accept"], names);
+        assert_eq!(vec!["rsproxy.com", "*/*"],
+                   request.headers_iter().map(|h| h.value()).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn http_request_write_to() {
+        let request = HttpRequest::new("PUT /upload HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        content-type: text/plain\r\n\
+                                        \r\n").unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        request.write_to(&mut buffer).unwrap();
+        assert_eq!(request.to_string().as_bytes(), &buffer[..]);
+        assert_eq!(request, HttpRequest::from_bytes(&buffer).unwrap());
+
+        let request = HttpRequest::from_bytes_with_body(
+            b"POST /submit HTTP/1.1\r\n\
+              Host: rsproxy.com\r\n\
+              Content-Length: 2\r\n\
+              \r\n\
+              hi").unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        request.write_to(&mut buffer).unwrap();
+        assert_eq!(request.to_bytes(), buffer);
+        assert!(buffer.ends_with(b"\r\n\r\nhi"));
+    }
+
+    #[test]
+    fn http_request_to_bytes() {
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n").unwrap();
+        assert_eq!(&b"GET / HTTP/1.1\r\nHost: rsproxy.com\r\n\r\n"[..],
+                   &request.to_bytes()[..]);
+        let bytes: Vec<u8> = request.clone().into();
+        assert_eq!(request, HttpRequest::from_bytes(&bytes).unwrap());
+
+        let request = HttpRequest::from_bytes_with_body(
+            b"POST /submit HTTP/1.1\r\n\
+              Host: rsproxy.com\r\n\
+              Content-Length: 5\r\n\
+              \r\n\
+              hello").unwrap();
+        let bytes = request.to_bytes();
+        assert!(bytes.ends_with(b"\r\n\r\nhello"));
+        assert_eq!(request, HttpRequest::from_bytes_with_body(&bytes).unwrap());
+        let mut head = request.clone();
+        head.body.clear();
+        assert_eq!(head, HttpRequest::from_bytes(&bytes).unwrap());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn http_request_write_to_with() {
+        use super::SerializeOptions;
+
+        let request = HttpRequest::new("GET / HTTP/1.1\r\n\
+                                        Host: rsproxy.com\r\n\
+                                        X-Request-ID: 42\r\n\
+                                        \r\n").unwrap();
+        let options = SerializeOptions { lowercase_header_names: true };
+        let mut buffer: Vec<u8> = Vec::new();
+        request.write_to_with(&mut buffer, &options).unwrap();
+        assert_eq!(&b"GET / HTTP/1.1\r\n\
+                      host: rsproxy.com\r\n\
+                      x-request-id: 42\r\n\
+                      \r\n"[..], &buffer[..]);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        request.write_to_with(&mut buffer, &SerializeOptions::default())
+               .unwrap();
+        assert_eq!(request.to_string().as_bytes(), &buffer[..]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn http_request_from_reader() {
+        use std::io::Cursor;
+        use std::io::ErrorKind;
+        use std::io::Read;
+
+        let mut reader = Cursor::new(&b"POST /submit HTTP/1.1\r\n\
+                                         Host: rsproxy.com\r\n\
+                                         Content-Length: 5\r\n\
+                                         \r\n\
+                                         hello"[..]);
+        let request = HttpRequest::from_reader(&mut reader).unwrap();
+        assert_eq!(&HttpMethod::Post, request.method());
+        assert_eq!(2, request.headers().len());
+        let mut body = String::new();
+        reader.read_to_string(&mut body).unwrap();
+        assert_eq!("hello", body);
+
+        let mut reader = Cursor::new(&b"GET / HTTP/1.1\r\nHost: rsp"[..]);
+        assert_eq!(Err(HttpParseError::Io(ErrorKind::UnexpectedEof)),
+                   HttpRequest::from_reader(&mut reader));
+    }
+}
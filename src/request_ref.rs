@@ -0,0 +1,271 @@
+use core::result::Result;
+#[cfg(feature = "alloc")]
+use core::slice;
+use core::str;
+
+#[cfg(feature = "alloc")]
+use alloc::string::ToString;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use error::HttpSyntaxError;
+#[cfg(feature = "alloc")]
+use header::HttpHeader;
+#[cfg(feature = "alloc")]
+use header::HttpHeaderName;
+#[cfg(feature = "alloc")]
+use method::HttpMethod;
+#[cfg(feature = "alloc")]
+use method::HttpVersion;
+#[cfg(feature = "alloc")]
+use request::HttpRequest;
+use syntax::check_line_endings;
+use syntax::head_len;
+use syntax::leading_empty_lines_len;
+use syntax::split_header_line;
+use syntax::split_request_line;
+
+// A request whose parts borrow from the parsed input instead of being copied
+// into owned strings. Headers are split off the header block on demand, so
+// parsing does not allocate and works without the alloc feature. Obsolete
+// line folding is rejected since a folded value can't be borrowed as one
+// slice. A view of an owned request borrows its header list instead.
+#[derive(Debug)]
+pub struct HttpRequestRef<'a> {
+    method:  &'a str,
+    uri:     &'a str,
+    version: &'a str,
+    headers: HeaderSource<'a>
+}
+
+#[derive(Debug)]
+enum HeaderSource<'a> {
+    Block(&'a str),
+    #[cfg(feature = "alloc")]
+    List(&'a [HttpHeader])
+}
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct HttpHeaderRef<'a> {
+    name:  &'a str,
+    value: &'a str
+}
+
+pub struct HttpHeaderRefs<'a> {
+    inner: HeaderRefsInner<'a>
+}
+
+enum HeaderRefsInner<'a> {
+    Lines(str::Split<'a, &'static str>),
+    #[cfg(feature = "alloc")]
+    List(slice::Iter<'a, HttpHeader>)
+}
+
+impl<'a> HttpRequestRef<'a> {
+    // Error offsets count from the start of `header`, leading empty lines
+    // included.
+    pub fn new(
+        header: &'a str
+    ) -> Result<HttpRequestRef<'a>, HttpSyntaxError> {
+        if header.trim().is_empty() {
+            return Result::Err(HttpSyntaxError::MissingRequestLine)
+        }
+        let offset = |part: &str| {
+            part.as_ptr() as usize - header.as_ptr() as usize
+        };
+        let skip = leading_empty_lines_len(header.as_bytes(), false);
+        let input = &header[skip ..];
+        check_line_endings(input).map_err(|e| e.shifted(skip))?;
+        // The head ends at the first empty line, which may directly follow
+        // the request line; anything after it is body and isn't scanned.
+        let head = &input[.. head_len(input.as_bytes())];
+        let head = head.strip_suffix("\r\n\r\n").unwrap_or(head);
+        let (request_line, headers) = match head.find("\r\n") {
+            Some(i) => (&head[.. i], &head[i + 2 ..]),
+            None    => (head, "")
+        };
+        let (method, uri, version) = split_request_line(request_line, false)
+            .map_err(|e| e.shifted(skip))?;
+        for line in headers.split("\r\n") {
+            if line.starts_with(' ') || line.starts_with('\t') {
+                return Err(HttpSyntaxError::MalformedHeader(offset(line)))
+            }
+            if !line.is_empty() {
+                split_header_line(line).map_err(|e| e.shifted(offset(line)))?;
+            }
+        }
+        Ok(HttpRequestRef {
+            method,
+            uri,
+            version,
+            headers: HeaderSource::Block(headers)
+        })
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn from_list(
+        method:  &'a str,
+        uri:     &'a str,
+        version: &'a str,
+        headers: &'a [HttpHeader]
+    ) -> HttpRequestRef<'a> {
+        HttpRequestRef {
+            method,
+            uri,
+            version,
+            headers: HeaderSource::List(headers)
+        }
+    }
+
+    pub fn method(&self) -> &'a str {
+        self.method
+    }
+
+    pub fn uri(&self) -> &'a str {
+        self.uri
+    }
+
+    pub fn version(&self) -> &'a str {
+        self.version
+    }
+
+    pub fn headers(&self) -> HttpHeaderRefs<'a> {
+        let inner = match self.headers {
+            HeaderSource::Block(block) => {
+                HeaderRefsInner::Lines(block.split("\r\n"))
+            }
+            #[cfg(feature = "alloc")]
+            HeaderSource::List(list)   => HeaderRefsInner::List(list.iter())
+        };
+        HttpHeaderRefs { inner }
+    }
+
+    pub fn header_str(&self, name: &str) -> Option<&'a str> {
+        self.headers()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value)
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> HttpRequest {
+        HttpRequest {
+            method:  self.method.parse::<HttpMethod>()
+                         .unwrap_or_else(|_| {
+                             HttpMethod::Extension(self.method.to_string())
+                         }),
+            uri:     self.uri.to_string(),
+            version: HttpVersion::new(self.version),
+            headers: self.headers().map(|h| h.to_owned()).collect(),
+            body:    Vec::new()
+        }
+    }
+}
+
+// Views compare by content, so a view of a parsed block equals a view of the
+// owned request built from it.
+impl<'a> PartialEq for HttpRequestRef<'a> {
+    fn eq(&self, other: &HttpRequestRef<'a>) -> bool {
+        self.method == other.method &&
+            self.uri == other.uri &&
+            self.version == other.version &&
+            self.headers().eq(other.headers())
+    }
+}
+
+impl<'a> HttpHeaderRef<'a> {
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn value(&self) -> &'a str {
+        self.value
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn to_owned(&self) -> HttpHeader {
+        HttpHeader {
+            name:          HttpHeaderName::new(self.name),
+            original_name: self.name.to_string(),
+            value:         self.value.to_string()
+        }
+    }
+}
+
+impl<'a> Iterator for HttpHeaderRefs<'a> {
+    type Item = HttpHeaderRef<'a>;
+
+    fn next(&mut self) -> Option<HttpHeaderRef<'a>> {
+        match self.inner {
+            HeaderRefsInner::Lines(ref mut lines) => {
+                for line in lines {
+                    if let Ok((name, value)) = split_header_line(line) {
+                        return Some(HttpHeaderRef { name, value })
+                    }
+                }
+                None
+            }
+            #[cfg(feature = "alloc")]
+            HeaderRefsInner::List(ref mut list)   => {
+                list.next().map(|h| HttpHeaderRef {
+                    name:  &h.original_name,
+                    value: &h.value
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::HttpSyntaxError;
+    #[cfg(feature = "alloc")]
+    use request::HttpRequest;
+    use super::HttpRequestRef;
+
+    #[test]
+    fn http_request_ref_borrowed() {
+        let request_str = "GET /some/path HTTP/1.1\r\n\
+                           Host: rsproxy.com\r\n\
+                           Accept: text/html\r\n\
+                           \r\n\
+                           body: not a header";
+        let request = HttpRequestRef::new(request_str).unwrap();
+        assert_eq!("GET", request.method());
+        assert_eq!("/some/path", request.uri());
+        assert_eq!("HTTP/1.1", request.version());
+        let mut headers = request.headers().map(|h| (h.name(), h.value()));
+        assert_eq!(Some(("Host", "rsproxy.com")), headers.next());
+        assert_eq!(Some(("Accept", "text/html")), headers.next());
+        assert_eq!(None, headers.next());
+        assert_eq!(Some("rsproxy.com"), request.header_str("HOST"));
+        assert_eq!(None, request.header_str("body"));
+        #[cfg(feature = "alloc")]
+        assert_eq!(HttpRequest::new(request_str).unwrap(), request.to_owned());
+    }
+
+    #[test]
+    fn http_request_ref_errors() {
+        assert_eq!(Err(HttpSyntaxError::MissingRequestLine),
+                   HttpRequestRef::new("\r\n"));
+        assert_eq!(Err(HttpSyntaxError::MalformedRequestLine(0)),
+                   HttpRequestRef::new("GET\r\n"));
+        assert_eq!(Err(HttpSyntaxError::MissingHeaderColon(16)),
+                   HttpRequestRef::new("GET / HTTP/1.1\r\nHost\r\n"));
+        assert_eq!(Err(HttpSyntaxError::MalformedHeader(22)),
+                   HttpRequestRef::new(
+                       "GET / HTTP/1.1\r\nA: b\r\n folded\r\n"));
+        assert_eq!(Err(HttpSyntaxError::InvalidHeaderName(18)),
+                   HttpRequestRef::new("\r\nGET / HTTP/1.1\r\nA b: c\r\n"));
+        assert_eq!(Err(HttpSyntaxError::InvalidMethod(2)),
+                   HttpRequestRef::new("\r\nG\"T / HTTP/1.1\r\n"));
+        let request = HttpRequestRef::new("GET / HTTP/1.1").unwrap();
+        assert_eq!(0, request.headers().count());
+        let request = HttpRequestRef::new("GET / HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(0, request.headers().count());
+        let request_str = "GET / HTTP/1.0\r\n\r\nbody\r\n\r\n";
+        let request = HttpRequestRef::new(request_str).unwrap();
+        assert_eq!(0, request.headers().count());
+        #[cfg(feature = "alloc")]
+        assert_eq!(HttpRequest::new(request_str).unwrap(), request.to_owned());
+    }
+}
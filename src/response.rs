@@ -0,0 +1,125 @@
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::result::Result;
+
+use error::HttpParseError;
+use header::HttpHeader;
+use header::HttpHeaderSplitTrim;
+use header::parse_headers;
+use method::HttpVersion;
+use syntax::check_line_endings;
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct HttpResponse {
+    version: HttpVersion,
+    status:  u16,
+    reason:  String,
+    headers: Vec<HttpHeader>
+}
+
+impl HttpResponse {
+    pub fn new(header: &str) -> Result<HttpResponse, HttpParseError> {
+        // As for requests, a lone LF would let a header hide in the line
+        // before it.
+        check_line_endings(header).map_err(|e| e.into_parse_error(header))?;
+        let lines: Vec<&str> = header.split("\r\n").collect();
+        let status_line = lines[0].splitn_trim(3, " ");
+        if status_line.len() < 2 {
+            return Result::Err(
+                HttpParseError::MalformedStatusLine(lines[0].to_string()))
+        }
+        let version = HttpVersion::new(status_line[0]);
+        let code = status_line[1];
+        if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Result::Err(
+                HttpParseError::InvalidStatusCode(code.to_string()))
+        }
+        let status = code.parse::<u16>().unwrap();
+        let reason = status_line.get(2).unwrap_or(&"").to_string();
+        if reason.chars().any(|c| c.is_control() && c != '\t') {
+            return Result::Err(
+                HttpParseError::MalformedStatusLine(lines[0].to_string()))
+        }
+        let headers = parse_headers(&lines[1 ..], false)?;
+
+        Result::Ok(HttpResponse { version, status, reason, headers })
+    }
+
+    pub fn version(&self) -> &HttpVersion {
+        &self.version
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn headers(&self) -> &[HttpHeader] {
+        &self.headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use error::HttpParseError;
+    use method::HttpVersion;
+    use header::HttpHeaderName;
+    use super::HttpResponse;
+
+    #[test]
+    fn http_response_status_line() {
+        let response_str = "HTTP/1.1 404 Not Found\r\n\
+                            Content-Type: text/html\r\n";
+        let response = HttpResponse::new(response_str).unwrap();
+        assert_eq!(HttpVersion::Http11, response.version);
+        assert_eq!(404, response.status);
+        assert_eq!("Not Found", response.reason);
+        assert_eq!(HttpHeaderName::ContentType, response.headers[0].name);
+        assert_eq!("text/html", response.headers[0].value);
+    }
+
+    #[test]
+    fn http_response_accessors() {
+        let response = HttpResponse::new("HTTP/1.0 200 OK\r\n\
+                                          Server: rsproxy\r\n").unwrap();
+        assert_eq!(&HttpVersion::Http10, response.version());
+        assert_eq!(200, response.status());
+        assert_eq!("OK", response.reason());
+        assert_eq!(1, response.headers().len());
+        assert_eq!("rsproxy", response.headers()[0].value());
+    }
+
+    #[test]
+    fn http_response_invalid_status_code() {
+        assert_eq!(Err(HttpParseError::InvalidStatusCode("OK".to_string())),
+                   HttpResponse::new("HTTP/1.1 OK\r\n"));
+        assert!(HttpResponse::new("HTTP/1.1 2000 OK\r\n").is_err());
+        assert!(HttpResponse::new("HTTP/1.1 20 OK\r\n").is_err());
+        assert_eq!(
+            Err(HttpParseError::MalformedStatusLine("HTTP/1.1".to_string())),
+            HttpResponse::new("HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn http_response_line_endings() {
+        assert_eq!(Err(HttpParseError::BareLineFeed(15)),
+                   HttpResponse::new("HTTP/1.1 200 OK\nX: y\n"));
+        assert_eq!(Err(HttpParseError::BareLineFeed(21)),
+                   HttpResponse::new("HTTP/1.1 200 OK\r\nX: y\nZ: 1\r\n"));
+        assert_eq!(Err(HttpParseError::MalformedStatusLine(
+                       "HTTP/1.1 200 O\rK".to_string())),
+                   HttpResponse::new("HTTP/1.1 200 O\rK\r\n"));
+        assert_eq!(Err(HttpParseError::MalformedStatusLine(
+                       "HTTP/1.1 200 \0OK".to_string())),
+                   HttpResponse::new("HTTP/1.1 200 \0OK\r\n"));
+        let response = HttpResponse::new("HTTP/1.1 200 Fine\tthanks\r\n")
+            .unwrap();
+        assert_eq!("Fine\tthanks", response.reason());
+    }
+}
@@ -0,0 +1,177 @@
+use core::result::Result;
+
+use error::HttpSyntaxError;
+
+// The empty lines skipped before a request line unless ParseLimits says
+// otherwise.
+pub(crate) const DEFAULT_MAX_LEADING_EMPTY_LINES: usize = 8;
+
+// RFC 7230 3.5: a server should ignore at least one empty line received
+// before the request line, which some clients send after a POST body. Up to
+// `max` are skipped; any further ones are left to fail parsing.
+pub(crate) fn skip_empty_lines(
+    bytes:   &[u8],
+    max:     usize,
+    lenient: bool
+) -> usize {
+    let mut pos = 0;
+    for _ in 0 .. max {
+        if bytes[pos ..].starts_with(b"\r\n") {
+            pos += 2;
+        } else if lenient && bytes[pos ..].starts_with(b"\n") {
+            pos += 1;
+        } else {
+            break
+        }
+    }
+    pos
+}
+
+// The empty lines the default limits allow before a request line.
+pub(crate) fn leading_empty_lines_len(bytes: &[u8], lenient: bool) -> usize {
+    skip_empty_lines(bytes, DEFAULT_MAX_LEADING_EMPTY_LINES, lenient)
+}
+
+// Length of the header block including its terminating empty line, or the
+// whole input when it has no terminator.
+pub(crate) fn head_len(bytes: &[u8]) -> usize {
+    find(bytes, b"\r\n\r\n").map_or(bytes.len(), |i| i + 4)
+}
+
+pub(crate) fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// A lone LF may be treated as a line ending by one parser and as part of a
+// value by another, so it is rejected anywhere in the header block.
+pub(crate) fn check_line_endings(header: &str) -> Result<(), HttpSyntaxError> {
+    let head = &header.as_bytes()[.. head_len(header.as_bytes())];
+    match head.iter().enumerate().position(|(i, &b)| {
+        b == b'\n' && (i == 0 || head[i - 1] != b'\r')
+    }) {
+        Some(i) => Err(HttpSyntaxError::BareLineFeed(i)),
+        None    => Ok(())
+    }
+}
+
+// Strictly the tokens are separated by exactly one space, as the grammar
+// requires; tolerating other whitespace is a request smuggling vector when
+// another hop splits the line differently. Lenient parsing accepts any run of
+// whitespace.
+pub(crate) fn split_request_line(
+    line:    &str,
+    lenient: bool
+) -> Result<(&str, &str, &str), HttpSyntaxError> {
+    let malformed = HttpSyntaxError::MalformedRequestLine(0);
+    // A valid line has at most three tokens, so they fit without allocating.
+    let mut tokens = [(0, ""); 3];
+    let mut count = 0;
+    for token in request_line_tokens(line, lenient) {
+        if count == tokens.len() || token.1.contains(char::is_whitespace) {
+            return Err(malformed)
+        }
+        tokens[count] = token;
+        count += 1;
+    }
+    match tokens[.. count] {
+        // A doubled space or a dropped target leaves the version where the
+        // uri should be.
+        [_, (_, ""), (_, version)] | [_, (_, version)]
+            if version.starts_with("HTTP/") =>
+            Err(HttpSyntaxError::MissingUri),
+        [(method_index, method), (uri_index, uri), (_, version)]
+            if !method.is_empty() && !version.is_empty() => {
+            check_method(method, method_index)?;
+            check_uri(method, uri, uri_index)?;
+            Ok((method, uri, version))
+        },
+        // HTTP/0.9 simple requests have no version token.
+        [(method_index, method), (uri_index, uri)]
+            if !method.is_empty() && !uri.is_empty() => {
+            check_method(method, method_index)?;
+            check_uri(method, uri, uri_index)?;
+            Ok((method, uri, "HTTP/0.9"))
+        },
+        _ => Err(malformed)
+    }
+}
+
+// The tokens of a request line, each with its byte offset so errors can
+// point at the offending token.
+fn request_line_tokens(
+    line:    &str,
+    lenient: bool
+) -> impl Iterator<Item = (usize, &str)> {
+    let offset = move |token: &str| {
+        token.as_ptr() as usize - line.as_ptr() as usize
+    };
+    let separator = move |c: char| {
+        if lenient { c.is_whitespace() } else { c == ' ' }
+    };
+    line.split(separator)
+        .filter(move |token| !lenient || !token.is_empty())
+        .map(move |token| (offset(token), token))
+}
+
+fn check_method(method: &str, index: usize) -> Result<(), HttpSyntaxError> {
+    if is_token(method) {
+        Ok(())
+    } else {
+        Err(HttpSyntaxError::InvalidMethod(index))
+    }
+}
+
+// The target must be in origin, absolute or asterisk form; authority form
+// is only meaningful for CONNECT.
+pub(crate) fn check_uri(
+    method: &str,
+    uri:    &str,
+    index:  usize
+) -> Result<(), HttpSyntaxError> {
+    if uri.starts_with('/') || uri == "*" || has_scheme(uri) ||
+        method.eq_ignore_ascii_case("CONNECT") {
+        Ok(())
+    } else {
+        Err(HttpSyntaxError::InvalidUri(index))
+    }
+}
+
+pub(crate) fn has_scheme(uri: &str) -> bool {
+    uri.find(':').is_some_and(|i| is_scheme(&uri[.. i]))
+}
+
+pub(crate) fn is_scheme(scheme: &str) -> bool {
+    !scheme.is_empty() && scheme.chars().enumerate().all(|(i, c)| {
+        c.is_ascii_alphabetic() ||
+            (i > 0 && (c.is_ascii_digit() || c == '+' || c == '-' || c == '.'))
+    })
+}
+
+pub(crate) fn split_header_line(
+    line: &str
+) -> Result<(&str, &str), HttpSyntaxError> {
+    let i = match line.find(':') {
+        Some(i) => i,
+        None    => return Err(HttpSyntaxError::MissingHeaderColon(0))
+    };
+    // No whitespace is allowed between the field name and the colon, a
+    // parser that strips it can be made to disagree with one that doesn't.
+    let name = &line[.. i];
+    if !is_token(name) {
+        return Err(HttpSyntaxError::InvalidHeaderName(0))
+    }
+    Ok((name, trim_ows(&line[i + 1 ..])))
+}
+
+// Strips the optional whitespace (spaces and tabs) around a field value,
+// anything else is part of the value.
+pub(crate) fn trim_ows(value: &str) -> &str {
+    value.trim_matches(|c| c == ' ' || c == '\t')
+}
+
+// RFC 7230 token: one or more tchar.
+pub(crate) fn is_token(token: &str) -> bool {
+    !token.is_empty() && token.bytes().all(|b| {
+        b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+    })
+}